@@ -0,0 +1,238 @@
+//! Settings from a `fixture.toml`, applying to the tests of its folder
+//! and every folder below, with nearer files overriding farther ones.
+
+use std::{
+    fs,
+    format_args as fmt,
+    path::Path,
+    time::Duration,
+};
+
+use proc_macro2::TokenStream;
+use quote::{
+    quote,
+    ToTokens,
+};
+use syn::{
+    Meta,
+    parse_str,
+};
+use toml::Value;
+
+use super::{
+    options::parse_duration,
+    traits::*,
+};
+
+/// The name of the file configuring its folder and those below.
+pub(super) const CONFIG_FILE: &str = "fixture.toml";
+
+/// The name of the file marking the tests of its folder `#[ignore]`,
+/// with any contents as the reason.
+pub(super) const IGNORE_MARKER: &str = "input.ignore";
+
+/// The name of the file marking the tests of its folder as known
+/// failures, with any contents as the reason.
+pub(super) const XFAIL_MARKER: &str = "input.xfail";
+
+/// Whether the tests of a folder are marked, as ignored or as known
+/// failures, with an optional reason.
+#[derive(Clone)]
+pub(super) enum Marker {
+    No,
+    Yes,
+    Because(String),
+}
+
+impl Marker {
+    /// The marker as an `Option<&str>` expression, with `""` as the
+    /// reason of an unexplained marker.
+    pub(super) fn reason(&self) -> TokenStream {
+        match self {
+            Marker::No => quote! { std::option::Option::None },
+            Marker::Yes => quote! { std::option::Option::Some("") },
+            Marker::Because(reason) => quote! { std::option::Option::Some(#reason) },
+        }
+    }
+}
+
+/// The settings of one `fixture.toml`, or of every `fixture.toml` from
+/// the base folder down once [`Config::under`] its parent.
+///
+/// Unset values are `None`, so that they are inherited.
+#[derive(Clone, Default)]
+pub(super) struct Config {
+    /// `ignore = true`, or `ignore = "reason"`
+    pub(super) ignore: Option<Marker>,
+    /// `xfail = true`, or `xfail = "reason"`, to pass while the result
+    /// differs from the expected file and fail once it matches
+    pub(super) xfail: Option<Marker>,
+    /// `panics = true`, to fail unless the fixture panics, or `false`,
+    /// to fail if it does
+    pub(super) panics: Option<bool>,
+    /// `timeout = "5s"`
+    pub(super) timeout: Option<Duration>,
+    /// `render = ["sorted"]`
+    pub(super) sorted: Option<bool>,
+    /// `tags = ["slow"]`
+    pub(super) tags: Option<Vec<String>>,
+    /// `cfg = 'feature = "unicode"'`, which is only for the folder it is
+    /// in, as the tests below are inside the folder's module anyway
+    pub(super) cfg: Option<Meta>,
+}
+
+impl Config {
+    /// The settings in the `fixture.toml` of `dir`, if it has one.
+    pub(super) fn load(dir: &Path) -> Result<Option<Config>, TokenStream> {
+        let file = dir.join(CONFIG_FILE);
+        if !file.is_file() {
+            return Ok(None)
+        }
+        let contents = fs::read_to_string(&file)
+            .compile_error(fmt!("Failed to read {:?}", file))?;
+        let table = match contents
+            .parse::<Value>()
+            .compile_error(fmt!("Failed to parse {:?}", file))?
+        {
+            Value::Table(table) => table,
+            _ => return ().compile_error(fmt!("Expected a table in {:?}", file)),
+        };
+
+        let mut config = Config::default();
+        for (key, value) in table {
+            let unexpected = |expected: &str| ().compile_error(fmt!(
+                "Expected {} = {} in {:?}, received: {}",
+                key,
+                expected,
+                file,
+                value,
+            ));
+            match (key.as_str(), &value) {
+                ("ignore" | "xfail", value) => {
+                    let marker = match value {
+                        Value::Boolean(true) => Marker::Yes,
+                        Value::Boolean(false) => Marker::No,
+                        Value::String(reason) => Marker::Because(reason.clone()),
+                        _ => return unexpected("true, false, or \"reason\""),
+                    };
+                    if key == "ignore" {
+                        config.ignore = Some(marker);
+                    } else {
+                        config.xfail = Some(marker);
+                    }
+                },
+                ("panics", Value::Boolean(panics)) => config.panics = Some(*panics),
+                ("panics", _) => return unexpected("true or false"),
+                ("timeout", Value::String(timeout)) => config.timeout = Some(parse_duration(timeout)?),
+                ("timeout", _) => return unexpected("\"duration\""),
+                ("render", Value::Array(modes)) => {
+                    let mut sorted = false;
+                    for mode in modes {
+                        match mode.as_str() {
+                            Some("sorted") => sorted = true,
+                            _ => return unexpected("[\"sorted\"]"),
+                        }
+                    }
+                    config.sorted = Some(sorted);
+                },
+                ("render", _) => return unexpected("[\"sorted\"]"),
+                ("tags", Value::Array(tags)) => match tags
+                    .iter()
+                    .map(|tag| tag.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+                {
+                    Some(tags) => config.tags = Some(tags),
+                    None => return unexpected("[\"tag\", ...]"),
+                },
+                ("tags", _) => return unexpected("[\"tag\", ...]"),
+                ("cfg", Value::String(cfg)) => config.cfg = Some(
+                    parse_str(cfg).compile_error(fmt!("Invalid cfg {:?} in {:?}", cfg, file))?
+                ),
+                ("cfg", _) => return unexpected("'predicate'"),
+                _ => return ().compile_error(fmt!("Unrecognized setting {} in {:?}", key, file)),
+            }
+        }
+        Ok(Some(config))
+    }
+
+    /// The marker of a marker `file`, with its contents as the reason.
+    pub(super) fn marker(file: &Path) -> Result<Marker, TokenStream> {
+        let reason = fs::read_to_string(file)
+            .compile_error(fmt!("Failed to read {:?}", file))?;
+        let reason = reason.trim();
+        Ok(if reason.is_empty() {
+            Marker::Yes
+        } else {
+            Marker::Because(reason.to_string())
+        })
+    }
+
+    /// These settings, inheriting any that are unset from `parent`.
+    pub(super) fn under(&self, parent: &Config) -> Config {
+        Config {
+            ignore: self.ignore.clone().or_else(|| parent.ignore.clone()),
+            xfail: self.xfail.clone().or_else(|| parent.xfail.clone()),
+            panics: self.panics.or(parent.panics),
+            timeout: self.timeout.or(parent.timeout),
+            sorted: self.sorted.or(parent.sorted),
+            tags: self.tags.clone().or_else(|| parent.tags.clone()),
+            cfg: self.cfg.clone(),
+        }
+    }
+
+    /// The `#[ignore]` and `#[cfg(...)]` of a test.
+    ///
+    /// A test whose tags are not chosen by `FN_FIXTURE_TAGS` is ignored
+    /// too, so that it is reported as such rather than as passing.
+    pub(super) fn test_attributes(&self) -> TokenStream {
+        let ignore = match &self.ignore {
+            Some(Marker::Yes) => quote! { #[ignore] },
+            Some(Marker::Because(reason)) => quote! { #[ignore = #reason] },
+            Some(Marker::No) | None if !chosen(self.tags.as_deref().unwrap_or(&[])) =>
+                quote! { #[ignore = "Not chosen by FN_FIXTURE_TAGS"] },
+            Some(Marker::No) | None => TokenStream::new(),
+        };
+        let cfg = self.module_attributes();
+        quote! { #cfg #ignore }
+    }
+
+    /// The `#[cfg(...)]` of a folder's module.
+    pub(super) fn module_attributes(&self) -> TokenStream {
+        match &self.cfg {
+            Some(cfg) => {
+                let cfg = cfg.to_token_stream();
+                quote! { #[cfg(#cfg)] }
+            },
+            None => TokenStream::new(),
+        }
+    }
+}
+
+/// The variable choosing tests by their `tags`.
+pub(super) const TAGS_VARIABLE: &str = "FN_FIXTURE_TAGS";
+
+/// Whether a test with `tags` runs, as chosen by the comma-separated
+/// `FN_FIXTURE_TAGS` when the tests are built.
+///
+/// A test with any tag listed as `!tag` is skipped, and when any tag is
+/// listed without a `!`, a test needs one of them to run.
+fn chosen(tags: &[String]) -> bool {
+    let chosen = match std::env::var(TAGS_VARIABLE) {
+        Ok(chosen) => chosen,
+        Err(_) => return true,
+    };
+    let mut included = false;
+    let mut any_included = false;
+    for tag in chosen.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        match tag.strip_prefix('!') {
+            Some(excluded) => if tags.iter().any(|tag| tag == excluded) {
+                return false
+            },
+            None => {
+                any_included = true;
+                included |= tags.iter().any(|chosen| chosen == tag);
+            },
+        }
+    }
+    included || !any_included
+}
@@ -0,0 +1,129 @@
+//! Skips the files and folders of a tree that are not part of it: any
+//! starting with `.`, and any matched by a `.fixtureignore`.
+
+use std::{
+    fs,
+    format_args as fmt,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use glob::Pattern;
+use proc_macro2::TokenStream;
+
+use super::{
+    traits::*,
+    GLOB,
+};
+
+/// The name of the file listing the patterns to ignore in its folder
+/// and below.
+const IGNORE_FILE: &str = ".fixtureignore";
+
+/// A line of a `.fixtureignore`, in the style of `.gitignore`.
+#[derive(Clone)]
+struct Rule {
+    /// The folder of the `.fixtureignore`.
+    dir: PathBuf,
+    pattern: Pattern,
+    /// With a `/` before the end, the pattern is matched against the
+    /// path from `dir`, otherwise against the name alone.
+    anchored: bool,
+    /// With a trailing `/`, only folders match.
+    dir_only: bool,
+    /// With a leading `!`, a match is not ignored after all.
+    negated: bool,
+}
+
+/// The rules of every `.fixtureignore` from the base folder down.
+#[derive(Default)]
+pub(super) struct Ignore {
+    rules: Vec<Rule>,
+}
+
+impl Ignore {
+    /// The rules for the entries of `dir`, adding those of its
+    /// `.fixtureignore`, if any.
+    pub(super) fn child(&self, dir: &Path) -> Result<Ignore, TokenStream> {
+        let mut rules = Vec::new();
+        let ignore_file = dir.join(IGNORE_FILE);
+        if ignore_file.is_file() {
+            let contents = fs::read_to_string(&ignore_file)
+                .compile_error(fmt!("Failed to read {:?}", ignore_file))?;
+            for line in contents.lines() {
+                if let Some(rule) = Rule::parse(dir, line)
+                    .compile_error(fmt!("Invalid pattern {:?} in {:?}", line, ignore_file))?
+                {
+                    rules.push(rule);
+                }
+            }
+        }
+        Ok(Ignore {
+            rules: self
+                .rules
+                .iter()
+                .cloned()
+                .chain(rules)
+                .collect(),
+        })
+    }
+
+    /// Whether the entry `name` of `dir` is skipped.
+    pub(super) fn ignores(&self, dir: &Path, name: &str, is_dir: bool) -> bool {
+        if name.starts_with('.') {
+            return true
+        }
+        // The last rule to match decides, as with `.gitignore`
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(dir, name, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+impl Rule {
+    fn parse(dir: &Path, line: &str) -> Result<Option<Rule>, glob::PatternError> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None)
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        Ok(Some(Rule {
+            dir: dir.to_path_buf(),
+            pattern: Pattern::new(line.strip_prefix('/').unwrap_or(line))?,
+            anchored,
+            dir_only,
+            negated,
+        }))
+    }
+
+    fn matches(&self, dir: &Path, name: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false
+        }
+        if !self.anchored {
+            return self.pattern.matches_with(name, GLOB)
+        }
+        let relative = match dir.strip_prefix(&self.dir) {
+            Ok(relative) => relative
+                .iter()
+                .map(|component| component.to_string_lossy())
+                .chain(Some(name.into()))
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => return false,
+        };
+        self.pattern.matches_with(&relative, GLOB)
+    }
+}
@@ -4,33 +4,49 @@
 //! [`fn-fixture`]: https://docs.rs/fn-fixture/
 
 use std::{
+    cell::{
+        Cell,
+        RefCell,
+    },
+    collections::BTreeSet,
     cmp::Ordering,
     env::var,
     fs::DirEntry,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
     format_args as fmt,
+    time::Duration,
 };
 
+use glob::{
+    MatchOptions,
+    Pattern,
+};
 use proc_macro2::{
     Ident,
     Literal,
     Span,
     TokenStream,
 };
-use smallvec::SmallVec;
 use syn::{
-    ExprLit,
     FnArg,
+    GenericParam,
     ItemFn,
-    Lit,
     parse2,
     parse_str,
+    Meta,
     Pat,
     PatIdent,
     PatType,
     Signature,
     Type,
+    TypePath,
+    TypeReference,
     Generics,
+    LifetimeDef,
+    TypeParam,
 };
 
 use quote::{
@@ -38,13 +54,41 @@ use quote::{
     ToTokens,
 };
 
+mod config;
+mod ignore;
+mod options;
+mod strict;
 mod traits;
 
-use self::traits::*;
+use self::{
+    config::{
+        Config,
+        CONFIG_FILE,
+        IGNORE_MARKER,
+        TAGS_VARIABLE,
+        XFAIL_MARKER,
+    },
+    ignore::Ignore,
+    options::{
+        Options,
+        Output,
+        Redaction,
+    },
+    strict::Strict,
+    traits::*,
+};
 
 const INPUT_TXT: &str = "input.txt";
 const INPUT_RS: &str = "input.rs";
 const INPUT_BIN: &str = "input.bin";
+const INPUT_DIR: &str = "input";
+const OUTPUT_DIR: &str = ".out";
+/// `*` stays within a folder, while `**` crosses them.
+const GLOB: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
 
 #[doc(hidden)]
 pub fn make_snapshots(path_attr: &TokenStream, item: &TokenStream) -> Result<TokenStream, TokenStream> {
@@ -57,40 +101,92 @@ pub fn make_snapshots(path_attr: &TokenStream, item: &TokenStream) -> Result<Tok
             where_clause: generic_where,
         },
         (param_name, param_type),
+        extras,
     ) = pull_function_description(item.clone())?;
 
+    let Options {
+        path,
+        output,
+        compare,
+        float_tolerance,
+        redactions,
+        sort_collections,
+        panic_payloads,
+        panic_location,
+        panic_backtrace,
+        isolate,
+        mut timeout,
+        mut timeouts,
+        determinism,
+        capture_stdout,
+        capture_stderr,
+        capture_log,
+        capture_tracing,
+        resources,
+        strict,
+        variants,
+    } = Options::parse(path_attr)?;
+
     let actual_file_name = {
         let mut base_name = name.to_string();
-        base_name.push_str(".actual.txt");
+        base_name.push_str(".actual.");
+        base_name.push_str(output.extension());
         base_name
     };
     let expected_file_name = {
         let mut base_name = name.to_string();
-        base_name.push_str(".txt");
+        base_name.push('.');
+        base_name.push_str(output.extension());
         base_name
     };
+    // Each variant of the expected file, chosen when its predicate holds
+    let variant_files: Vec<(String, Meta)> = variants
+        .iter()
+        .map(|(key, predicate)| (format!("{}.{}.{}", name, key, output.extension()), predicate.clone()))
+        .collect();
 
-    if expected_file_name == INPUT_TXT {
-        return ().compile_error(fmt!("Cannot use that name, as it conflicts with {} detection", INPUT_TXT))
+    if let Some(input) = [INPUT_TXT, INPUT_RS, INPUT_BIN]
+        .iter()
+        .find(|&&input| input == expected_file_name)
+    {
+        return ().compile_error(fmt!("Cannot use that name, as it conflicts with {} detection", input))
     }
 
     let base_name = name.to_token_stream();
 
     let path = {
-        let mut path = PathBuf::new();
-        path.push(var("CARGO_MANIFEST_DIR").compile_err("No manifest directory env")?);
-        path.push(
-            if let Lit::Str(string) =
-            parse2::<ExprLit>(path_attr.clone())
-                .compile_error(fmt!("Expected literal path in attribute, received: {}\n\n", path_attr))?
-                .lit
-            {
-                string.value()
-            } else {
-                return ().compile_error(fmt!("Expected literal path: {}", path_attr))
-            }
-        );
-        path
+        let mut full_path = PathBuf::new();
+        full_path.push(var("CARGO_MANIFEST_DIR").compile_err("No manifest directory env")?);
+        full_path.push(path);
+        full_path
+    };
+
+    let embedded = !resources.is_empty();
+    let strict_variants = strict.is_some();
+    let strict = match strict {
+        Some(allowed) => Some(Strict::new(
+            Path::new(&var("CARGO_MANIFEST_DIR").compile_err("No manifest directory env")?),
+            path.canonicalize().compile_error(fmt!("Failed to canonicalize {:?}", path))?,
+            allowed,
+            &name.to_string(),
+            output.extension(),
+            &variants,
+        )),
+        None => None,
+    };
+
+    let root = path.canonicalize().unwrap_or_else(|_| path.clone());
+    let ignore = Ignore::default().child(&root)?;
+    // Settings of each folder are kept for those chosen as the test runs
+    let configured = RefCell::new(Vec::new());
+    let found_variants = RefCell::new(BTreeSet::new());
+    let (copied, included) = (Cell::new(false), Cell::new(false));
+    let config = match Config::load(&root)? {
+        Some(config) => {
+            configured.borrow_mut().push((String::new(), config.clone()));
+            config
+        },
+        None => Config::default(),
     };
 
     let tag: TokenStream = "#[test]".parse().compile_err("Failed to init tag")?;
@@ -102,94 +198,549 @@ pub fn make_snapshots(path_attr: &TokenStream, item: &TokenStream) -> Result<Tok
             .compile_error(fmt!("Failed to read {:?}", path))?
         )
             .into_iter()
+            .filter(|result| match result {
+                Ok(entry) => entry.file_name() != CONFIG_FILE && !ignore.ignores(
+                    &root,
+                    &entry.file_name().to_string_lossy(),
+                    entry.file_type().is_ok_and(|kind| kind.is_dir()),
+                ),
+                Err(_) => true,
+            })
             .map(|result|
                 result.compile_error(fmt!("Failed to read in {:?}", path))
             ),
         &TokenStream::new(),
+        &ignore,
+        &Config::default().under(&config),
         &Params {
             tag,
             base_name,
             supers,
             actual_file_name,
             expected_file_name,
+            variant_files,
+            resources,
+            strict,
+            root: root.clone(),
+            configured: &configured,
+            found_variants: &found_variants,
+            copied: &copied,
+            included: &included,
         }
     );
+    let configured = configured.into_inner();
+    // A copied directory is handed over differently than an included file
+    let copied = copied.get();
+    if copied && included.get() {
+        return ().compile_error(fmt!(
+            "Input directories and input files are mixed in {:?}",
+            path,
+        ))
+    }
+    // A variant with no file anywhere is likely misspelled
+    if strict_variants {
+        let found_variants = found_variants.into_inner();
+        let unused: Vec<_> = variants
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| !found_variants.contains(&format!("{}.{}.{}", name, key, output.extension())))
+            .collect();
+        if !unused.is_empty() {
+            return ().compile_error(fmt!(
+                "No file of variants {:?} in {:?}",
+                unused,
+                path,
+            ))
+        }
+    }
+    let root_attributes = config.module_attributes();
+
+    let render = output.renderer();
+    let comparator = compare.comparator(output, float_tolerance);
+    // The nearest folder with a setting decides it for a test
+    let select = |default: TokenStream, overrides: Vec<(&String, TokenStream)>| {
+        let (folders, values): (Vec<_>, Vec<_>) = overrides.into_iter().unzip();
+        quote! {
+            ::fn_fixture::runtime::select(
+                test_path,
+                concat!(module_path!(), "::", stringify!(#name)),
+                #default,
+                &[#((#folders, #values)),*],
+            )
+        }
+    };
+    let sorted: Vec<_> = configured
+        .iter()
+        .filter_map(|(folder, config)| config.sorted.map(|sorted| (folder, quote! { #sorted })))
+        .collect();
+    let panics: Vec<_> = configured
+        .iter()
+        .filter_map(|(folder, config)| config.panics.map(|panics| (folder, quote! { #panics })))
+        .collect();
+    // The tags are chosen as the tests build, so a change of the
+    // variable must rebuild them, which reading it through
+    // `option_env!` asks of cargo
+    let tracked_tags = if configured.iter().any(|(_, config)| config.tags.is_some()) {
+        quote! {
+            let _ = option_env!(#TAGS_VARIABLE);
+        }
+    } else {
+        TokenStream::new()
+    };
+    timeouts.extend(configured
+        .iter()
+        .filter_map(|(folder, config)| config.timeout.map(|timeout| (folder.clone(), timeout)))
+    );
+    if let Some((_, root_timeout)) = configured.first().filter(|(folder, _)| folder.is_empty()) {
+        timeout = root_timeout.timeout.or(timeout);
+    }
+    let xfails: Vec<_> = configured
+        .iter()
+        .filter_map(|(folder, config)| config.xfail.as_ref().map(|xfail| (folder, xfail.reason())))
+        .collect();
+    let selecting = !sorted.is_empty() || !panics.is_empty() || !xfails.is_empty();
+    if !sorted.is_empty() && !matches!(output, Output::Debug) {
+        return ().compile_err("Render modes require output of \"debug\"")
+    }
+    if !panics.is_empty() && isolate {
+        return ().compile_err(if capture_stdout || capture_stderr {
+            "Expected panics of fixture.toml are unsupported with isolate, which capture(stdout, stderr) implies"
+        } else {
+            "Expected panics of fixture.toml are unsupported with isolate"
+        })
+    }
+
+    let sort = if !sorted.is_empty() {
+        let sorted = select(quote! { std::option::Option::Some(#sort_collections) }, sorted);
+        quote! {
+            let result = if #sorted.unwrap_or(false) {
+                ::fn_fixture::runtime::render::sort_collections(result)
+            } else {
+                result
+            };
+        }
+    } else if sort_collections {
+        quote! {
+            let result = ::fn_fixture::runtime::render::sort_collections(result);
+        }
+    } else {
+        TokenStream::new()
+    };
+    let location = if panic_location {
+        quote! {
+            let result = ::fn_fixture::runtime::sections::location(result, &caught.panic, env!("CARGO_MANIFEST_DIR"));
+        }
+    } else {
+        TokenStream::new()
+    };
+    let backtrace = if panic_backtrace {
+        quote! {
+            let result = ::fn_fixture::runtime::sections::backtrace(result, &caught.panic, module_path!());
+        }
+    } else {
+        TokenStream::new()
+    };
+    let redact = if redactions.is_empty() {
+        TokenStream::new()
+    } else {
+        let normalizers = redactions.iter().map(Redaction::normalizer);
+        quote! {
+            let result = ::fn_fixture::runtime::redact::apply(result, &[#(#normalizers),*]);
+        }
+    };
+
+    // The fixture borrows each extra for the call, and the extras are
+    // read once the call returns or panics
+    let mut extra_setup = TokenStream::new();
+    let mut extra_args = Vec::new();
+    let mut recorded = TokenStream::new();
+    for extra in &extras {
+        match extra {
+            Extra::Recorder => {
+                extra_setup.extend(quote! {
+                    let mut recorder = ::fn_fixture::runtime::sections::Recorder::default();
+                    let recording = std::panic::AssertUnwindSafe(&mut recorder);
+                });
+                extra_args.push(quote! { &mut *{ recording }.0 });
+                recorded = quote! {
+                    let result = ::fn_fixture::runtime::sections::recorded(result, recorder);
+                };
+            },
+            Extra::Context => extra_args.push(quote! { &context }),
+            Extra::OutputDir => {
+                extra_setup.extend(quote! {
+                    let output_dir = ::fn_fixture::runtime::output_dir::OutputDir::new(actual_file);
+                });
+                extra_args.push(quote! { &output_dir });
+            },
+        }
+    }
+    // Payloads render under the path as written, which unlike
+    // `type_name` does not change between compiler versions.
+    let payload_names = panic_payloads
+        .iter()
+        .map(|payload| {
+            let segments = payload
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            match payload.leading_colon {
+                Some(_) => format!("::{}", segments),
+                None => segments,
+            }
+        });
+    // The copy is owned by the call, and removed once the call is done
+    let (provided, owned, input) = if copied {
+        (
+            quote! { ::fn_fixture::runtime::input::Copied },
+            quote! { let input = temp.unwrap(); },
+            quote! { &input },
+        )
+    } else {
+        (param_type.to_token_stream(), TokenStream::new(), quote! { temp.unwrap() })
+    };
+    let invoke = quote! {
+        ::fn_fixture::runtime::catch(
+            &[#((#payload_names, ::fn_fixture::runtime::payload::<#panic_payloads>)),*],
+            #panic_backtrace,
+            move || {
+                let mut temp = std::option::Option::None;
+                provider(&mut temp);
+                #owned
+                to_call(#input #(, #extra_args)*)
+            }
+        )
+    };
+    // Each capture wraps the call, adding what it captured to the pattern
+    let mut pattern = quote! { caught };
+    let mut sections = TokenStream::new();
+    let mut section = |name: &str, captured: TokenStream| sections.extend(quote! {
+        let result = ::fn_fixture::runtime::sections::append(result, #name, &#captured);
+    });
+    let mut invoke = invoke;
+    if capture_tracing {
+        invoke = quote! { ::fn_fixture::runtime::events::tracing(|| #invoke) };
+        pattern = quote! { (#pattern, tracing) };
+    }
+    if capture_log {
+        invoke = quote! { ::fn_fixture::runtime::events::log(|| #invoke) };
+        pattern = quote! { (#pattern, log) };
+    }
+    // The platform is that of the tests, known only where they build
+    let unsupported = if capture_stdout || capture_stderr {
+        invoke = quote! { ::fn_fixture::runtime::output::capture(#capture_stdout, #capture_stderr, || #invoke) };
+        pattern = quote! { (#pattern, output) };
+        quote! {
+            #[cfg(not(unix))]
+            compile_error! { "capture(stdout, stderr) is only supported on Unix" }
+        }
+    } else {
+        TokenStream::new()
+    };
+    if capture_stdout {
+        section("stdout", quote! { output.stdout });
+    }
+    if capture_stderr {
+        section("stderr", quote! { output.stderr });
+    }
+    if capture_log {
+        section("log", quote! { log });
+    }
+    if capture_tracing {
+        section("tracing", quote! { tracing });
+    }
+    let invoke = quote! {
+        #extra_setup
+        let #pattern = #invoke;
+        let result = ::fn_fixture::runtime::render::#render(caught.outcome);
+        #sort
+        #recorded
+        #sections
+    };
+    // Redacted in each run, so that runs differing only in what is
+    // redacted are the same
+    let once = quote! {
+        #invoke
+        #location
+        #backtrace
+        #redact
+        let panic = caught.panic;
+    };
+    // Every run gets a fresh input from the provider
+    let catch = match determinism {
+        // The files written are compared between runs as well
+        Some(runs) if extras.contains(&Extra::OutputDir) => quote! {
+            let provider = &provider;
+            let (result, panic) = ::fn_fixture::runtime::determinism::repeat_writing(#runs, actual_file, || { #once (result, panic) });
+        },
+        Some(runs) => quote! {
+            let provider = &provider;
+            let (result, panic) = ::fn_fixture::runtime::determinism::repeat(#runs, || { #once (result, panic) });
+        },
+        None => once,
+    };
+    let timed = timeout.is_some() || !timeouts.is_empty();
+    for (folder, _) in &timeouts {
+        if !path.join(folder.replace("::", "/")).is_dir() {
+            return ().compile_error(fmt!("No folder {} in {:?} for timeout", folder, path))
+        }
+    }
+    let timeout = if timed {
+        // Durations are parsed as a u64 of millis, so none are truncated
+        let millis = |duration: &Duration| duration.as_millis() as u64;
+        let default = match &timeout {
+            Some(timeout) => {
+                let timeout = millis(timeout);
+                quote! { std::option::Option::Some(std::time::Duration::from_millis(#timeout)) }
+            },
+            None => quote! { std::option::Option::None },
+        };
+        let folders = timeouts.iter().map(|(folder, _)| folder);
+        let durations = timeouts.iter().map(|(_, duration)| millis(duration));
+        quote! {
+            ::fn_fixture::runtime::timeout::select(
+                test_path,
+                concat!(module_path!(), "::", stringify!(#name)),
+                #default,
+                &[#((#folders, std::time::Duration::from_millis(#durations))),*],
+            )
+        }
+    } else {
+        quote! { std::option::Option::None }
+    };
+    let contextual = extras.contains(&Extra::Context);
+    let test_path = if isolate || timed || contextual || selecting {
+        quote! { test_path }
+    } else {
+        quote! { _ }
+    };
+    let context = if contextual {
+        let resources = if embedded {
+            quote! { resources }
+        } else {
+            quote! { &[] }
+        };
+        quote! {
+            let context = ::fn_fixture::runtime::context::Context::new(
+                test_path,
+                concat!(module_path!(), "::", stringify!(#name)),
+                expected_file,
+                #resources,
+            );
+        }
+    } else {
+        TokenStream::new()
+    };
+    // Resources are only passed along when the attribute declares them
+    let (resources_type, resources) = match (embedded, contextual) {
+        (false, _) => (TokenStream::new(), TokenStream::new()),
+        (true, true) => (quote! { &'static [(&'static str, &'static [u8])], }, quote! { resources, }),
+        (true, false) => (quote! { &'static [(&'static str, &'static [u8])], }, quote! { _, }),
+    };
+    // Off the test's thread, the fixture must be sendable. The bounds
+    // go on the generated function alone, whose generics are concrete
+    // in every test, so the fixture's own generics stay as written.
+    // They are spanned to the attribute, so that lints about bounds
+    // written in two places pass them by.
+    let (send, generic_where) = if timed && !isolate {
+        let existing = generic_where
+            .iter()
+            .flat_map(|clause| clause.predicates.iter());
+        let bounds = generic_params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Type(TypeParam { ident, .. }) => {
+                    let ident = Ident::new(&ident.to_string(), Span::call_site());
+                    Some(quote! { #ident: 'static })
+                },
+                GenericParam::Lifetime(LifetimeDef { lifetime, .. }) => {
+                    let mut lifetime = lifetime.clone();
+                    lifetime.ident = Ident::new(&lifetime.ident.to_string(), Span::call_site());
+                    Some(quote! { #lifetime: 'static })
+                },
+                GenericParam::Const(_) => None,
+            });
+        (
+            quote! { + std::marker::Send + 'static },
+            quote! { where #(#existing,)* #(#bounds,)* },
+        )
+    } else {
+        (TokenStream::new(), generic_where.into_token_stream())
+    };
+    let extension = output.extension();
+    // Whether the fixture returned is only needed to compare its files
+    let writing = extras.contains(&Extra::OutputDir);
+    let completed = if writing {
+        quote! { completed }
+    } else {
+        quote! { _ }
+    };
+    // Isolated, the child renders and the parent only sees the result
+    let call = if isolate {
+        quote! {
+            let (result, #completed) = match ::fn_fixture::runtime::isolate::run(test_path, #timeout, move || { #catch result }) {
+                std::result::Result::Ok(result) => (result, true),
+                std::result::Result::Err(interrupted) => (interrupted.render(#extension), false),
+            };
+            let panic = std::option::Option::None;
+        }
+    } else if timed {
+        quote! {
+            let (result, panic, #completed) = match ::fn_fixture::runtime::timeout::run(#timeout, move || { #catch (result, panic) }) {
+                std::result::Result::Ok((result, panic)) => (result, panic, true),
+                std::result::Result::Err(interrupted) => (interrupted.render(#extension), std::option::Option::None, false),
+            };
+        }
+    } else {
+        quote! {
+            #catch
+        }
+    };
 
-    // <String> panics come from the formatted panic!, including .unwrap/.expect
-    // <&str> panics come from unformatted panic!, like panic!("Nooo!")
+    let checked = quote! {
+        ::fn_fixture::runtime::check(
+            &result,
+            &panic,
+            expected_file,
+            actual_file,
+            &#comparator,
+        )
+    };
+    // The files are compared first, so that both actuals are written,
+    // but reported after the result
+    // An interrupted fixture may still be writing, so only its result
+    // is checked
+    let checked = if writing {
+        let compare = quote! {
+            ::fn_fixture::runtime::output_dir::compare(expected_file, actual_file)
+        };
+        let compare = if isolate || timed {
+            quote! {
+                if completed {
+                    #compare
+                } else {
+                    std::result::Result::Ok(())
+                }
+            }
+        } else {
+            compare
+        };
+        quote! {
+            let compared = #compare;
+            #checked;
+            ::fn_fixture::runtime::output_dir::check(compared)
+        }
+    } else {
+        checked
+    };
+    let checked = if panics.is_empty() {
+        checked
+    } else {
+        let panics = select(quote! { std::option::Option::None }, panics);
+        quote! {
+            #checked;
+            ::fn_fixture::runtime::expect_panic(#panics, &panic)
+        }
+    };
+    let checked = if xfails.is_empty() {
+        checked
+    } else {
+        let xfail = select(quote! { std::option::Option::None }, xfails);
+        quote! {
+            ::fn_fixture::runtime::expect_failure(
+                #xfail.flatten(),
+                expected_file,
+                std::panic::AssertUnwindSafe(|| { #checked }),
+            )
+        }
+    };
+
+    // A gated base folder gates the fixture along with its tests
     Ok(quote! {
+        #unsupported
+
+        #root_attributes
         fn #name #generic_lt #generic_params #generic_gt (mut #param_name: (
-            impl std::ops::Fn(&mut std::option::Option<#param_type>) + std::panic::RefUnwindSafe + std::panic::UnwindSafe,
+            impl std::ops::Fn(&mut std::option::Option<#provided>) + std::panic::RefUnwindSafe + std::panic::UnwindSafe #send,
+            &'static str,
             &'static str,
             &'static str,
+            #resources_type
          )) #generic_where {
             #item
 
-            let (to_call, (provider, expected_file, actual_file)) =
+            let (to_call, (provider, #test_path, expected_file, actual_file, #resources)) =
                 (&#name, #param_name);
 
-            let result = format!(
-                "{:#?}\n",
-                std::panic::catch_unwind(
-                    move || {
-                        let mut temp = std::option::Option::None;
-                        provider(&mut temp);
-                        to_call(temp.unwrap())
-                    }
-                ).map_err(|err| err
-                    .downcast::<String>()
-                    .or_else(|err|
-                        if let Some(string) = err.downcast_ref::<&str>() {
-                            std::result::Result::Ok(std::boxed::Box::new(string.to_string()))
-                        } else {
-                            std::result::Result::Err(("<!String> Panic", err))
-                        }
-                    )
-                    .map(|ok| ("<String> Panic", ok))
-                )
-            );
-            if std::path::Path::new(expected_file).is_file() {
-                let expected = std::fs::read_to_string(expected_file)
-                    .unwrap_or_else(|err|
-                        panic!("Reading expected from {}: {:?}", expected_file, err)
-                    );
-                assert_eq!(result, expected)
-            } else {
-                std::fs::write(actual_file, result.as_bytes())
-                    .unwrap_or_else(|err|
-                        panic!("Writing actual to {}: {:?}", actual_file, err)
-                    );
-                panic!("No expected value set: {}", actual_file)
-            }
+            #tracked_tags
+            #context
+            #call
+            #checked
         }
 
+        #root_attributes
         mod #name {
             #outputs
         }
     })
 }
 
-fn pull_function_description(item: TokenStream) -> Result<(Ident, Generics, (Ident, Type)), TokenStream> {
+/// A parameter after the input, recognized by the path of its type.
+///
+/// The path must be written out as `fn_fixture::Context`, with or
+/// without a leading `::`, so that a type of the fixture's own crate
+/// that happens to share the name is not taken over.
+#[derive(PartialEq)]
+enum Extra {
+    /// `&mut fn_fixture::Recorder`
+    Recorder,
+    /// `&fn_fixture::Context`
+    Context,
+    /// `&fn_fixture::OutputDir`
+    OutputDir,
+}
+
+impl Extra {
+    fn recognize(ty: &Type) -> Option<Extra> {
+        let (mutable, ty) = match ty {
+            Type::Reference(TypeReference { mutability, elem, .. }) => (mutability.is_some(), &**elem),
+            _ => return None,
+        };
+        let name = match ty {
+            Type::Path(TypePath { qself: None, path }) => match path.segments.iter().collect::<Vec<_>>()[..] {
+                [krate, name] if krate.ident == "fn_fixture" && krate.arguments.is_empty() && name.arguments.is_empty() =>
+                    name.ident.to_string(),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        match (mutable, &*name) {
+            (true, "Recorder") => Some(Extra::Recorder),
+            (false, "Context") => Some(Extra::Context),
+            (false, "OutputDir") => Some(Extra::OutputDir),
+            _ => None,
+        }
+    }
+}
+
+/// The name, generics, input, and extra parameters of the fixture.
+type FunctionDescription = (Ident, Generics, (Ident, Type), Vec<Extra>);
+
+fn pull_function_description(item: TokenStream) -> Result<FunctionDescription, TokenStream> {
     let Signature {
         ident: name,
-        inputs: param,
+        inputs: params,
         generics,
         ..
     } = parse2::<ItemFn>(item.clone())
         .compile_error(fmt!("Expected attribute must be on a function, received: {}\n\n", item))?
         .sig;
-    let param: SmallVec<[FnArg; 1]> = param.into_iter().collect();
-    let param = match param.into_inner() {
-        Ok([param]) => param,
-        Err(ref param) if param.is_empty() => return ().compile_err("No input parameter"),
-        Err(param) => return ().compile_error(fmt!(
-            "Expected one parameter, received {}",
-            param
-                .into_iter()
-                .map(FnArg::into_token_stream)
-                .flatten()
-                .collect::<TokenStream>()
-        )),
+    let mut params = params.into_iter();
+    let param = match params.next() {
+        Some(param) => param,
+        None => return ().compile_err("No input parameter"),
     };
     let (param_type, param_name) = match param {
         FnArg::Typed(PatType { pat, ty, .. }) => (*ty, *pat),
@@ -202,20 +753,54 @@ fn pull_function_description(item: TokenStream) -> Result<(Ident, Generics, (Ide
     if format!("{}", param_name) == format!("{}", name) {
         return ().compile_error(fmt!("Function {} may not share name with its parameter", name));
     }
-    Ok((name, generics, (param_name, param_type)))
+    let mut extras = Vec::new();
+    for param in params {
+        let extra = match &param {
+            FnArg::Typed(PatType { ty, .. }) => Extra::recognize(ty),
+            FnArg::Receiver(_) => None,
+        };
+        let extra = match extra {
+            Some(extra) => extra,
+            None => return ().compile_error(fmt!(
+                "Expected parameters after the input to be &mut fn_fixture::Recorder, &fn_fixture::Context, or &fn_fixture::OutputDir, received {}",
+                param.into_token_stream(),
+            )),
+        };
+        if extras.contains(&extra) {
+            return ().compile_error(fmt!("Duplicate parameter {}", param.into_token_stream()));
+        }
+        extras.push(extra);
+    }
+    Ok((name, generics, (param_name, param_type), extras))
 }
 
-struct Params {
+struct Params<'a> {
     tag: TokenStream,
     base_name: TokenStream,
     supers: TokenStream,
     actual_file_name: String,
     expected_file_name: String,
+    /// The name of each variant of the expected file, with its predicate.
+    variant_files: Vec<(String, Meta)>,
+    resources: Vec<Pattern>,
+    strict: Option<Strict>,
+    /// The base folder, which folders of settings are relative to.
+    root: PathBuf,
+    /// The settings of each folder with a `fixture.toml`.
+    configured: &'a RefCell<Vec<(String, Config)>>,
+    /// The names of the variant files found in the tree.
+    found_variants: &'a RefCell<BTreeSet<String>>,
+    /// Whether any test has an `input/` directory.
+    copied: &'a Cell<bool>,
+    /// Whether any test has an input file.
+    included: &'a Cell<bool>,
 }
 
 fn nested_fixtures(
     folders: impl IntoIterator<Item=Result<DirEntry, TokenStream>>,
     super_chain: &TokenStream,
+    ignore: &Ignore,
+    config: &Config,
     params: &Params,
 ) -> TokenStream {
     let Params {
@@ -224,6 +809,14 @@ fn nested_fixtures(
         supers,
         actual_file_name,
         expected_file_name,
+        variant_files,
+        resources,
+        strict,
+        root,
+        configured,
+        found_variants,
+        copied,
+        included,
     } = params;
     let super_chain = {
         let mut super_chain = super_chain.clone();
@@ -242,11 +835,33 @@ fn nested_fixtures(
                 .to_str()
                 .compile_error(fmt!("Failed to convert filename to utf8 of {:?}", fixture))?,
             ).compile_error(fmt!("Failed to convert filename of {:?} into rust identifier", fixture_path))?;
+            let ignore = ignore.child(&fixture_path)?;
+            // Folders of settings are named as test paths from the base folder
+            let folder = || fixture_path
+                .strip_prefix(root)
+                .compile_error(fmt!("Folder {:?} outside of {:?}", fixture_path, root))
+                .map(|folder| folder
+                    .iter()
+                    .map(|component| component.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("::")
+                );
+            let mut config = match Config::load(&fixture_path)? {
+                Some(own) => {
+                    let config = own.under(config);
+                    configured.borrow_mut().push((folder()?, own));
+                    config
+                },
+                None => Config::default().under(config),
+            };
 
             let mut input_rs = None;
             let mut input_txt = None;
             let mut input_bin = None;
+            let mut input_dir = None;
             let mut folders: Option<Vec<_>> = None;
+            let mut unexpected = Vec::new();
+            let mut marked = Config::default();
 
             for file in sort_dir(fixture_path
                 .read_dir()
@@ -266,9 +881,25 @@ fn nested_fixtures(
                     file.compile_error(fmt!("Failed to get DirEntry in {:?}", fixture_path))
                 );
 
-                if push_err!(
+                let is_dir = push_err!(
                     file.file_type().compile_error(fmt!("Bad file type of {:?}", file))
-                ).is_dir() {
+                ).is_dir();
+                if ignore.ignores(&fixture_path, &file.file_name().to_string_lossy(), is_dir) {
+                    continue;
+                }
+
+                if is_dir {
+                    if file.file_name() == INPUT_DIR {
+                        input_dir = Some(file);
+                        continue;
+                    }
+                    // Expected and actual output directories of any fixture
+                    if let Some(name) = file.file_name().to_str().filter(|name| name.ends_with(OUTPUT_DIR)) {
+                        if strict.as_ref().is_some_and(|strict| !strict.allows(&file.path(), name)) {
+                            unexpected.push(name.to_string());
+                        }
+                        continue;
+                    }
                     folders
                         .get_or_insert_with(Vec::new)
                         .push(Ok(file));
@@ -284,11 +915,62 @@ fn nested_fixtures(
                     INPUT_RS => &mut input_rs,
                     INPUT_TXT => &mut input_txt,
                     INPUT_BIN => &mut input_bin,
-                    _ => continue,
+                    CONFIG_FILE => continue,
+                    IGNORE_MARKER => {
+                        marked.ignore = Some(push_err!(Config::marker(&file.path())));
+                        continue
+                    },
+                    XFAIL_MARKER => {
+                        marked.xfail = Some(push_err!(Config::marker(&file.path())));
+                        continue
+                    },
+                    name if variant_files.iter().any(|(variant, _)| variant == name) => {
+                        found_variants.borrow_mut().insert(name.to_string());
+                        continue
+                    },
+                    name => {
+                        let resource = resources.iter().any(|glob| glob.matches_with(name, GLOB));
+                        if !resource && strict.as_ref().is_some_and(|strict| !strict.allows(&file.path(), name)) {
+                            unexpected.push(name.to_string());
+                        }
+                        continue
+                    },
                 };
                 *file_pointer = Some(file);
             }
 
+            // Markers override the fixture.toml of their own folder
+            if marked.ignore.is_some() || marked.xfail.is_some() {
+                if marked.ignore.is_some() {
+                    config.ignore = marked.ignore.clone();
+                }
+                if marked.xfail.is_some() {
+                    config.xfail = marked.xfail.clone();
+                }
+                configured.borrow_mut().push((folder()?, marked));
+            }
+
+            // Beside an input, folders holding resources belong to the test
+            let mut embedded = Vec::new();
+            if !resources.is_empty() && (input_rs.is_some() || input_bin.is_some() || input_txt.is_some() || input_dir.is_some()) {
+                match find_resources(&fixture_path, &fixture_path, params) {
+                    Ok(found) => embedded = found,
+                    Err(err) => folders.get_or_insert_with(Vec::new).push(Err(err)),
+                }
+                if let Some(list) = &mut folders {
+                    list.retain(|folder| match folder {
+                        Ok(folder) => {
+                            let prefix = format!("{}/", folder.file_name().to_string_lossy());
+                            !embedded.iter().any(|(name, _)| name.starts_with(&prefix))
+                        },
+                        Err(_) => true,
+                    });
+                }
+                if folders.as_ref().is_some_and(Vec::is_empty) {
+                    folders = None;
+                }
+            }
+
             match (
                 folders.as_ref().map_or(
                     true,
@@ -298,14 +980,16 @@ fn nested_fixtures(
                 &input_rs,
                 &input_bin,
                 &input_txt,
+                &input_dir,
             ) {
                 // No vec and one file
                 // Vec with error and one file
-                (true, None, Some(_), None) => {},
-                (true, None, None, Some(_)) => {},
-                (true, Some(_), None, None) => {},
+                (true, None, Some(_), None, None) => {},
+                (true, None, None, Some(_), None) => {},
+                (true, Some(_), None, None, None) => {},
+                (true, None, None, None, Some(_)) => {},
                 // Vec without errors and no files
-                (false, None, None, None) => {},
+                (false, None, None, None, None) => {},
                 // Vec with error and multiple files
                 // Vec with error and no files
                 // No vec and no files
@@ -313,57 +997,120 @@ fn nested_fixtures(
                 _ => folders
                     .get_or_insert_with(Vec::new)
                     .push(().compile_error(fmt!(
-                        "Expected sub-directories or exactly one of {}, {}, or {} in {:?}",
+                        "Expected sub-directories or exactly one of {}, {}, {}, or {}/ in {:?}",
                         INPUT_RS,
                         INPUT_BIN,
                         INPUT_TXT,
+                        INPUT_DIR,
                         fixture_path,
                     ))),
             }
 
-            let (include, file) = match (folders, input_rs, input_bin, input_txt) {
+            if !unexpected.is_empty() {
+                folders
+                    .get_or_insert_with(Vec::new)
+                    .push(().compile_error(fmt!(
+                        "Unexpected files in {:?}: {}",
+                        fixture_path,
+                        unexpected.join(", "),
+                    )));
+            }
+
+            let (include, file) = match (folders, input_rs, input_bin, input_txt, input_dir) {
                 // dir
-                (Some(folders), _, _, _) => {
+                (Some(folders), _, _, _, _) => {
                     let fixtures = nested_fixtures(
                         folders,
                         &super_chain,
+                        &ignore,
+                        &config,
                         params,
                     );
+                    let attributes = config.module_attributes();
                     return Ok(quote! {
+                        #attributes
                         mod #fixture_name {
                             #fixtures
                         }
                     })
                 },
                 // rs
-                (None, Some(file), None, None) => ("include", file),
+                (None, Some(file), None, None, None) => ("include", file),
                 // bin
-                (None, None, Some(file), None) => ("include_bytes", file),
+                (None, None, Some(file), None, None) => ("include_bytes", file),
                 // txt
-                (None, None, None, Some(file)) => ("include_str", file),
+                (None, None, None, Some(file), None) => ("include_str", file),
+                // input directory
+                (None, None, None, None, Some(file)) => ("Copied", file),
                 // If there wasn't a single-file, folders would be populated
                 _ => unreachable!(),
             };
-            // Can't panic; we have them explicitly outlined
-            let include= Ident::new(include, Span::call_site());
-
             let make_literal = |path: PathBuf| path
                 .to_str()
                 .compile_error(fmt!("Failed to get utf8 string from {:?}", path))
                 .map(Literal::string);
             let input_literal = make_literal(file.path())?;
+            // Can't panic; we have them explicitly outlined
+            let include= Ident::new(include, Span::call_site());
+            // A copy of the directory is made each run, and every file
+            // in it is included so that changing one rebuilds the test
+            let (input, tracked) = if file.file_type().is_ok_and(|kind| kind.is_dir()) {
+                let tracked = files_in(&file.path())?
+                    .into_iter()
+                    .map(make_literal)
+                    .collect::<Result<Vec<_>, _>>()?;
+                copied.set(true);
+                (
+                    quote! { ::fn_fixture::runtime::input::#include::of(#input_literal) },
+                    quote! { let _: &[&[u8]] = &[#(include_bytes!(#tracked)),*]; },
+                )
+            } else {
+                included.set(true);
+                (quote! { #include!(#input_literal) }, TokenStream::new())
+            };
             let actual_literal = make_literal(fixture_path.join(actual_file_name))?;
-            let expected_literal = make_literal(fixture_path.join(expected_file_name))?;
+            // The variant is chosen as the test runs, from those present
+            let expected_literal = make_literal(fixture_path.join(expected_file_name))?.into_token_stream();
+            let expected_literal = if variant_files.is_empty() {
+                expected_literal
+            } else {
+                let predicates = variant_files.iter().map(|(_, predicate)| predicate);
+                let variants = variant_files
+                    .iter()
+                    .map(|(variant, _)| make_literal(fixture_path.join(variant)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                quote! {
+                    ::fn_fixture::runtime::variant(#expected_literal, &[#((cfg!(#predicates), #variants)),*])
+                }
+            };
+            let resources = if resources.is_empty() {
+                TokenStream::new()
+            } else {
+                let names = embedded.iter().map(|(name, _)| name);
+                let paths = embedded
+                    .iter()
+                    .map(|(_, path)| make_literal(path.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                quote! {
+                    &[#((#names, include_bytes!(#paths) as &'static [u8])),*],
+                }
+            };
+
+            let attributes = config.test_attributes();
 
             Ok(quote! {
                 #tag
+                #attributes
                 fn #fixture_name() {
+                    #tracked
                     #super_chain #base_name((
                         |#fixture_name: &mut std::option::Option<_>| {
-                            #fixture_name.replace(#include!(#input_literal));
+                            #fixture_name.replace(#input);
                         },
+                        concat!(module_path!(), "::", stringify!(#fixture_name)),
                         #expected_literal,
                         #actual_literal,
+                        #resources
                     ))
                 }
             })
@@ -372,6 +1119,60 @@ fn nested_fixtures(
         .collect()
 }
 
+/// The files under `dir` matching the resource globs, named by their
+/// path from `fixture_path` with `/` separators.
+fn find_resources(fixture_path: &Path, dir: &Path, params: &Params) -> Result<Vec<(String, PathBuf)>, TokenStream> {
+    let mut found = Vec::new();
+    for file in sort_dir(dir
+        .read_dir()
+        .compile_error(fmt!("Failed to read resource directory {:?}", dir))?
+    ) {
+        let file = file.compile_error(fmt!("Failed to get DirEntry in {:?}", dir))?;
+        let path = file.path();
+        if file.file_type().compile_error(fmt!("Bad file type of {:?}", file))?.is_dir() {
+            found.extend(find_resources(fixture_path, &path, params)?);
+            continue;
+        }
+        let name = path
+            .strip_prefix(fixture_path)
+            .compile_error(fmt!("Resource {:?} outside of {:?}", path, fixture_path))?
+            .iter()
+            .map(|component| component
+                .to_str()
+                .compile_error(fmt!("Failed to convert resource name to utf8 of {:?}", path))
+            )
+            .collect::<Result<Vec<_>, _>>()?
+            .join("/");
+        // The files of the test itself are never resources
+        if [INPUT_RS, INPUT_TXT, INPUT_BIN, CONFIG_FILE, IGNORE_MARKER, XFAIL_MARKER, &params.expected_file_name, &params.actual_file_name].contains(&&*name)
+            || params.variant_files.iter().any(|(variant, _)| *variant == name)
+        {
+            continue;
+        }
+        if params.resources.iter().any(|glob| glob.matches_with(&name, GLOB)) {
+            found.push((name, path));
+        }
+    }
+    Ok(found)
+}
+
+/// Every file under `dir`, in a stable order.
+fn files_in(dir: &Path) -> Result<Vec<PathBuf>, TokenStream> {
+    let mut files = Vec::new();
+    for file in sort_dir(dir
+        .read_dir()
+        .compile_error(fmt!("Failed to read input directory {:?}", dir))?
+    ) {
+        let file = file.compile_error(fmt!("Failed to get DirEntry in {:?}", dir))?;
+        if file.file_type().compile_error(fmt!("Bad file type of {:?}", file))?.is_dir() {
+            files.extend(files_in(&file.path())?);
+        } else {
+            files.push(file.path());
+        }
+    }
+    Ok(files)
+}
+
 fn sort_dir<T>(iter: impl IntoIterator<Item=Result<DirEntry, T>>) -> impl IntoIterator<Item=Result<DirEntry, T>> {
     let mut vec: Vec<_> = iter.into_iter().collect();
     vec.sort_by(|left, right| match (left, right) {
@@ -0,0 +1,529 @@
+use std::{
+    format_args as fmt,
+    time::Duration,
+};
+
+use glob::Pattern;
+use proc_macro2::{
+    Ident,
+    Span,
+    TokenStream,
+};
+use syn::{
+    Lit,
+    LitStr,
+    Meta,
+    MetaList,
+    MetaNameValue,
+    NestedMeta,
+    parse::Parser,
+    Path,
+    punctuated::Punctuated,
+    Token,
+};
+
+use quote::{
+    quote,
+    ToTokens,
+};
+
+use super::traits::*;
+
+/// Everything given to the attribute: the base folder, then any options.
+pub(super) struct Options {
+    pub(super) path: String,
+    pub(super) output: Output,
+    pub(super) compare: Compare,
+    pub(super) float_tolerance: Option<f64>,
+    pub(super) redactions: Vec<Redaction>,
+    pub(super) sort_collections: bool,
+    pub(super) panic_payloads: Vec<Path>,
+    pub(super) panic_location: bool,
+    pub(super) panic_backtrace: bool,
+    pub(super) isolate: bool,
+    pub(super) timeout: Option<Duration>,
+    pub(super) timeouts: Vec<(String, Duration)>,
+    pub(super) determinism: Option<usize>,
+    pub(super) capture_stdout: bool,
+    pub(super) capture_stderr: bool,
+    pub(super) capture_log: bool,
+    pub(super) capture_tracing: bool,
+    pub(super) resources: Vec<Pattern>,
+    pub(super) strict: Option<Vec<Pattern>>,
+    /// The key of each variant, with the predicate choosing it.
+    pub(super) variants: Vec<(String, Meta)>,
+}
+
+/// How the outcome of a fixture is rendered into its expected file.
+#[derive(Clone, Copy)]
+pub(super) enum Output {
+    Debug,
+    ErrorChain,
+    Json,
+    Yaml,
+    Ron,
+}
+
+impl Output {
+    /// Extension of both the expected and actual files.
+    pub(super) fn extension(self) -> &'static str {
+        match self {
+            Output::Debug | Output::ErrorChain => "txt",
+            Output::Json => "json",
+            Output::Yaml => "yaml",
+            Output::Ron => "ron",
+        }
+    }
+
+    /// Name of the renderer in `fn_fixture::runtime::render`.
+    pub(super) fn renderer(self) -> Ident {
+        Ident::new(
+            match self {
+                Output::Debug => "debug",
+                Output::ErrorChain => "error_chain",
+                Output::Json => "json",
+                Output::Yaml => "yaml",
+                Output::Ron => "ron",
+            },
+            Span::call_site(),
+        )
+    }
+}
+
+/// How the rendered result is compared to its expected file.
+#[derive(Clone, Copy)]
+pub(super) enum Compare {
+    Exact,
+    Structural,
+}
+
+impl Compare {
+    /// Expression constructing the comparator from `fn_fixture::runtime::compare`.
+    pub(super) fn comparator(self, output: Output, float_tolerance: Option<f64>) -> TokenStream {
+        let tolerance = float_tolerance.unwrap_or(0.0);
+        match (self, output, float_tolerance) {
+            (Compare::Structural, Output::Json, _) => quote! {
+                ::fn_fixture::runtime::compare::Json { float_tolerance: #tolerance }
+            },
+            (Compare::Structural, Output::Yaml, _) => quote! {
+                ::fn_fixture::runtime::compare::Yaml { float_tolerance: #tolerance }
+            },
+            (_, _, Some(_)) => quote! {
+                ::fn_fixture::runtime::compare::Tolerant { float_tolerance: #tolerance }
+            },
+            _ => quote! {
+                ::fn_fixture::runtime::compare::Exact
+            },
+        }
+    }
+}
+
+/// A normalizer applied to the rendered result, from `fn_fixture::runtime::redact`.
+pub(super) enum Redaction {
+    ManifestDir,
+    PathSeparators,
+    Addresses,
+    Uuids,
+    Timestamps,
+    Regex(LitStr, LitStr),
+    With(Path),
+}
+
+impl Redaction {
+    fn parse(arg: NestedMeta) -> Result<Redaction, TokenStream> {
+        Ok(match arg {
+            NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("manifest_dir") => Redaction::ManifestDir,
+            NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("path_separators") => Redaction::PathSeparators,
+            NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("addresses") => Redaction::Addresses,
+            NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("uuids") => Redaction::Uuids,
+            NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("timestamps") => Redaction::Timestamps,
+            NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. })) if path.is_ident("regex") => {
+                match nested.iter().collect::<Vec<_>>().as_slice() {
+                    [NestedMeta::Lit(Lit::Str(pattern)), NestedMeta::Lit(Lit::Str(replacement))] => {
+                        require("regex", cfg!(feature = "regex"), "`regex` redaction")?;
+                        #[cfg(feature = "regex")]
+                        if let Err(err) = regex::Regex::new(&pattern.value()) {
+                            return Err(syn::Error::new(pattern.span(), format_args!("Invalid redaction regex: {}", err))
+                                .to_compile_error())
+                        }
+                        Redaction::Regex(pattern.clone(), replacement.clone())
+                    },
+                    _ => return ().compile_error(fmt!(
+                        "Expected regex(\"pattern\", \"replacement\"), received: {}",
+                        arg.into_token_stream(),
+                    )),
+                }
+            },
+            NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. })) if path.is_ident("with") => {
+                match nested.iter().collect::<Vec<_>>().as_slice() {
+                    [NestedMeta::Meta(Meta::Path(normalizer))] => Redaction::With(normalizer.clone()),
+                    _ => return ().compile_error(fmt!(
+                        "Expected with(path::to::function), received: {}",
+                        arg.into_token_stream(),
+                    )),
+                }
+            },
+            arg => return ().compile_error(fmt!(
+                "Expected manifest_dir, path_separators, addresses, uuids, timestamps, regex, or with, received: {}",
+                arg.into_token_stream(),
+            )),
+        })
+    }
+
+    /// Expression constructing the normalizer.
+    pub(super) fn normalizer(&self) -> TokenStream {
+        match self {
+            Redaction::ManifestDir => quote! {
+                &::fn_fixture::runtime::redact::ManifestDir(env!("CARGO_MANIFEST_DIR"))
+            },
+            Redaction::PathSeparators => quote! { &::fn_fixture::runtime::redact::PathSeparators },
+            Redaction::Addresses => quote! { &::fn_fixture::runtime::redact::Addresses },
+            Redaction::Uuids => quote! { &::fn_fixture::runtime::redact::Uuids },
+            Redaction::Timestamps => quote! { &::fn_fixture::runtime::redact::Timestamps },
+            Redaction::Regex(pattern, replacement) => quote! {
+                &::fn_fixture::runtime::redact::Regex::new(#pattern, #replacement)
+            },
+            Redaction::With(normalizer) => quote! {
+                &(#normalizer as fn(&str) -> std::string::String)
+            },
+        }
+    }
+}
+
+impl Options {
+    pub(super) fn parse(path_attr: &TokenStream) -> Result<Options, TokenStream> {
+        let mut args = Punctuated::<NestedMeta, Token![,]>::parse_terminated
+            .parse2(path_attr.clone())
+            .compile_error(fmt!("Expected literal path in attribute, received: {}\n\n", path_attr))?
+            .into_iter();
+
+        let path = match args.next() {
+            Some(NestedMeta::Lit(Lit::Str(string))) => string.value(),
+            _ => return ().compile_error(fmt!("Expected literal path: {}", path_attr)),
+        };
+
+        let mut output = Output::Debug;
+        let mut compare = None;
+        let mut float_tolerance = None;
+        let mut redactions = Vec::new();
+        let mut sort_collections = false;
+        let mut error_chain = false;
+        let mut panic_payloads = Vec::new();
+        let mut panic_location = false;
+        let mut panic_backtrace = false;
+        let mut isolate = false;
+        let mut timeout = None;
+        let mut timeouts = Vec::new();
+        let mut determinism = None;
+        let mut capture_stdout = false;
+        let mut capture_stderr = false;
+        let mut capture_log = false;
+        let mut capture_tracing = false;
+        let mut resources = Vec::new();
+        let mut strict = None;
+        let mut variants: Vec<(String, Meta)> = Vec::new();
+
+        for arg in args {
+            match arg {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, lit: Lit::Str(ref value), .. }))
+                if path.is_ident("output") => {
+                    output = match value.value().as_str() {
+                        "debug" => Output::Debug,
+                        "json" => require("serde", cfg!(feature = "serde"), "`json` output").map(|_| Output::Json)?,
+                        "yaml" => require("serde", cfg!(feature = "serde"), "`yaml` output").map(|_| Output::Yaml)?,
+                        "ron" => require("serde", cfg!(feature = "serde"), "`ron` output").map(|_| Output::Ron)?,
+                        _ => return ().compile_error(fmt!(
+                            "Expected output of \"debug\", \"json\", \"yaml\", or \"ron\", received: {}",
+                            arg.into_token_stream(),
+                        )),
+                    };
+                },
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, lit: Lit::Str(ref value), .. }))
+                if path.is_ident("compare") => {
+                    compare = Some(match value.value().as_str() {
+                        "exact" => Compare::Exact,
+                        "structural" => Compare::Structural,
+                        _ => return ().compile_error(fmt!(
+                            "Expected compare of \"exact\" or \"structural\", received: {}",
+                            arg.into_token_stream(),
+                        )),
+                    });
+                },
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, ref lit, .. }))
+                if path.is_ident("float_tolerance") => {
+                    let tolerance = match lit {
+                        Lit::Float(float) => float.base10_parse::<f64>(),
+                        Lit::Int(int) => int.base10_parse::<f64>(),
+                        _ => return ().compile_error(fmt!(
+                            "Expected number for float_tolerance, received: {}",
+                            arg.into_token_stream(),
+                        )),
+                    }.compile_error(fmt!("Failed to parse float_tolerance"))?;
+                    if !tolerance.is_finite() || tolerance < 0.0 {
+                        return ().compile_error(fmt!(
+                            "Expected finite, non-negative float_tolerance, received: {}",
+                            tolerance,
+                        ))
+                    }
+                    float_tolerance = Some(tolerance);
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("redact") => {
+                    for redaction in nested {
+                        redactions.push(Redaction::parse(redaction.clone())?);
+                    }
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("render") => {
+                    for mode in nested {
+                        match mode {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("sorted") =>
+                                sort_collections = true,
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("error_chain") =>
+                                error_chain = true,
+                            mode => return ().compile_error(fmt!(
+                                "Expected render mode of sorted or error_chain, received: {}",
+                                mode.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("panic_payloads") => {
+                    for payload in nested {
+                        match payload {
+                            NestedMeta::Meta(Meta::Path(payload)) => panic_payloads.push(payload.clone()),
+                            payload => return ().compile_error(fmt!(
+                                "Expected panic_payloads(path::to::Type, ...), received: {}",
+                                payload.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("panic_details") => {
+                    for detail in nested {
+                        match detail {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("location") =>
+                                panic_location = true,
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("backtrace") =>
+                                panic_backtrace = true,
+                            detail => return ().compile_error(fmt!(
+                                "Expected panic detail of location or backtrace, received: {}",
+                                detail.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("isolate") => isolate = true,
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("capture") => {
+                    for stream in nested {
+                        match stream {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("stdout") =>
+                                capture_stdout = true,
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("stderr") =>
+                                capture_stderr = true,
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("log") => {
+                                require("log", cfg!(feature = "log"), "`log` capture")?;
+                                capture_log = true
+                            },
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("tracing") => {
+                                require("tracing", cfg!(feature = "tracing"), "`tracing` capture")?;
+                                capture_tracing = true
+                            },
+                            stream => return ().compile_error(fmt!(
+                                "Expected capture of stdout, stderr, log, or tracing, received: {}",
+                                stream.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, lit: Lit::Int(ref runs), .. }))
+                if path.is_ident("determinism") => {
+                    let runs = runs
+                        .base10_parse::<usize>()
+                        .compile_error(fmt!("Failed to parse determinism"))?;
+                    if runs < 2 {
+                        return ().compile_error(fmt!("Expected determinism of at least 2 runs, received: {}", runs))
+                    }
+                    determinism = Some(runs);
+                },
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref path, lit: Lit::Str(ref value), .. }))
+                if path.is_ident("timeout") => {
+                    timeout = Some(parse_duration(&value.value())?);
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("timeout") => {
+                    for folder in nested {
+                        match folder {
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit: Lit::Str(value), .. })) => {
+                                let folder = path
+                                    .segments
+                                    .iter()
+                                    .map(|segment| segment.ident.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("::");
+                                timeouts.push((folder, parse_duration(&value.value())?));
+                            },
+                            folder => return ().compile_error(fmt!(
+                                "Expected timeout(folder::path = \"duration\", ...), received: {}",
+                                folder.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("resources") => {
+                    for glob in nested {
+                        match glob {
+                            NestedMeta::Lit(Lit::Str(glob)) => resources.push(
+                                Pattern::new(&glob.value())
+                                    .compile_error(fmt!("Invalid resource glob {:?}", glob.value()))?
+                            ),
+                            glob => return ().compile_error(fmt!(
+                                "Expected resources(\"glob\", ...), received: {}",
+                                glob.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("strict") => {
+                    strict.get_or_insert_with(Vec::new);
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("strict") => {
+                    let allowed = strict.get_or_insert_with(Vec::new);
+                    for glob in nested {
+                        match glob {
+                            NestedMeta::Lit(Lit::Str(glob)) => allowed.push(
+                                Pattern::new(&glob.value())
+                                    .compile_error(fmt!("Invalid strict glob {:?}", glob.value()))?
+                            ),
+                            glob => return ().compile_error(fmt!(
+                                "Expected strict(\"glob\", ...), received: {}",
+                                glob.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                NestedMeta::Meta(Meta::List(MetaList { ref path, ref nested, .. }))
+                if path.is_ident("variants") => {
+                    for variant in nested {
+                        // `name` is keyed as itself, `name = "value"` as `name_value`
+                        let key = match variant {
+                            NestedMeta::Meta(Meta::Path(path)) => path.get_ident().map(Ident::to_string),
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit: Lit::Str(value), .. })) => path
+                                .get_ident()
+                                .map(|name| format!(
+                                    "{}_{}",
+                                    name,
+                                    value
+                                        .value()
+                                        .chars()
+                                        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                                        .collect::<String>(),
+                                )),
+                            _ => None,
+                        };
+                        match (key, variant) {
+                            (Some(key), NestedMeta::Meta(predicate)) => {
+                                if key == "actual" || variants.iter().any(|(known, _)| *known == key) {
+                                    return ().compile_error(fmt!("Variant {} conflicts with another file", key))
+                                }
+                                variants.push((key, predicate.clone()));
+                            },
+                            _ => return ().compile_error(fmt!(
+                                "Expected variants(name, name = \"value\", ...), received: {}",
+                                variant.into_token_stream(),
+                            )),
+                        }
+                    }
+                },
+                arg => return ().compile_error(fmt!("Unrecognized option: {}", arg.into_token_stream())),
+            }
+        }
+
+        // Structured formats are compared structurally unless asked otherwise
+        if let (Some(Compare::Exact), Some(_)) = (compare, float_tolerance) {
+            return ().compile_err("float_tolerance is incompatible with compare = \"exact\"")
+        }
+        let compare = match (compare, output) {
+            (None, Output::Json) | (None, Output::Yaml) => Compare::Structural,
+            (None, _) => Compare::Exact,
+            (Some(Compare::Structural), Output::Debug) | (Some(Compare::Structural), Output::Ron) =>
+                return ().compile_err("Structural comparison requires output of \"json\" or \"yaml\""),
+            (Some(compare), _) => compare,
+        };
+
+        if (sort_collections || error_chain) && !matches!(output, Output::Debug) {
+            return ().compile_err("Render modes require output of \"debug\"")
+        }
+        if error_chain {
+            output = Output::ErrorChain;
+        }
+        // The test harness keeps print! to itself, except in the child
+        if capture_stdout || capture_stderr {
+            isolate = true;
+        }
+
+        Ok(Options {
+            path,
+            output,
+            compare,
+            float_tolerance,
+            redactions,
+            sort_collections,
+            panic_payloads,
+            panic_location,
+            panic_backtrace,
+            isolate,
+            timeout,
+            timeouts,
+            determinism,
+            capture_stdout,
+            capture_stderr,
+            capture_log,
+            capture_tracing,
+            resources,
+            strict,
+            variants,
+        })
+    }
+}
+
+/// Fails unless the `feature` of fn-fixture that `what` needs is
+/// enabled, as it is forwarded to this crate.
+fn require(feature: &str, enabled: bool, what: &str) -> Result<(), TokenStream> {
+    if enabled {
+        Ok(())
+    } else {
+        ().compile_error(fmt!("{} requires the `{}` feature of fn-fixture", what, feature))
+    }
+}
+
+/// A whole number of milliseconds (`ms`), seconds (`s`), or minutes (`m`).
+pub(super) fn parse_duration(text: &str) -> Result<Duration, TokenStream> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let too_long = || ().compile_error(fmt!("Duration is too long, received: {:?}", text));
+    let number = match number.parse::<u64>() {
+        Ok(number) => number,
+        // Only digits are left, so the number is too big for a u64
+        Err(_) if !number.is_empty() => return too_long(),
+        Err(_) => return ().compile_error(fmt!("Expected duration like \"5s\", received: {:?}", text)),
+    };
+    // Timeouts are generated as millis, which must fit in a u64
+    let millis = match unit {
+        "ms" => Some(number),
+        "s" => number.checked_mul(1000),
+        "m" => number.checked_mul(60_000),
+        _ => return ().compile_error(fmt!("Expected duration in ms, s, or m, received: {:?}", text)),
+    };
+    match millis {
+        Some(millis) => Ok(Duration::from_millis(millis)),
+        None => too_long(),
+    }
+}
@@ -0,0 +1,137 @@
+//! Rejects files in a tree that no fixture of the crate would read or
+//! write, unless they are allowed by a glob.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use glob::Pattern;
+use proc_macro2::{
+    TokenStream,
+    TokenTree,
+};
+use syn::{
+    Attribute,
+    Item,
+    ItemFn,
+    ItemMod,
+    Meta,
+};
+
+use super::options::Options;
+
+/// The folders of a crate that hold its targets.
+const SOURCES: &[&str] = &["src", "tests", "benches", "examples"];
+
+pub(super) struct Strict {
+    /// The expected and actual files and folders of every fixture.
+    known: BTreeSet<String>,
+    /// Globs of other files, from the base folder.
+    allowed: Vec<Pattern>,
+    base: PathBuf,
+}
+
+impl Strict {
+    /// Knows the files of the fixture `name` and of every other fixture
+    /// found in the crate at `manifest_dir`.
+    pub(super) fn new(
+        manifest_dir: &Path,
+        base: PathBuf,
+        allowed: Vec<Pattern>,
+        name: &str,
+        extension: &str,
+        variants: &[(String, Meta)],
+    ) -> Strict {
+        let mut known = BTreeSet::new();
+        add_files(&mut known, name, extension, variants);
+        for source in SOURCES {
+            find_fixtures(&manifest_dir.join(source), &mut known);
+        }
+        Strict {
+            known,
+            allowed,
+            base,
+        }
+    }
+
+    /// Whether the file or folder at `path`, named `name`, is expected.
+    pub(super) fn allows(&self, path: &Path, name: &str) -> bool {
+        if self.known.contains(name) {
+            return true
+        }
+        let relative = match path.strip_prefix(&self.base) {
+            Ok(relative) => relative
+                .iter()
+                .map(|component| component.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => return false,
+        };
+        self.allowed.iter().any(|glob| glob.matches_with(&relative, super::GLOB))
+    }
+}
+
+fn add_files(known: &mut BTreeSet<String>, name: &str, extension: &str, variants: &[(String, Meta)]) {
+    known.insert(format!("{}.{}", name, extension));
+    known.insert(format!("{}.actual.{}", name, extension));
+    known.insert(format!("{}.out", name));
+    known.insert(format!("{}.actual.out", name));
+    for (key, _) in variants {
+        known.insert(format!("{}.{}.{}", name, key, extension));
+        known.insert(format!("{}.{}.out", name, key));
+    }
+}
+
+/// Adds the files of the fixtures in every rust file under `dir`.
+///
+/// Files that fail to read or parse are skipped, as the compiler
+/// reports them on its own.
+fn find_fixtures(dir: &Path, known: &mut BTreeSet<String>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_fixtures(&path, known);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            if let Some(file) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|source| syn::parse_file(&source).ok())
+            {
+                find_in_items(&file.items, known);
+            }
+        }
+    }
+}
+
+fn find_in_items(items: &[Item], known: &mut BTreeSet<String>) {
+    for item in items {
+        match item {
+            Item::Fn(ItemFn { attrs, sig, .. }) => {
+                for options in attrs.iter().filter_map(snapshot_options) {
+                    add_files(known, &sig.ident.to_string(), options.output.extension(), &options.variants);
+                }
+            },
+            Item::Mod(ItemMod { content: Some((_, items)), .. }) => find_in_items(items, known),
+            _ => {},
+        }
+    }
+}
+
+/// The options of a `#[snapshot(...)]` attribute.
+fn snapshot_options(attr: &Attribute) -> Option<Options> {
+    if attr.path.segments.last()?.ident != "snapshot" {
+        return None
+    }
+    match attr.tokens.clone().into_iter().next()? {
+        TokenTree::Group(group) => Options::parse(&group.stream()).ok(),
+        _ => Options::parse(&TokenStream::new()).ok(),
+    }
+}
@@ -0,0 +1,17 @@
+// Every test of fn-fixture builds this crate with all of its features,
+// so the errors for a missing one are only checked when it is tested
+// alone, as with `cargo test -p fn-fixture-lib`.
+
+#[test]
+#[cfg_attr(feature = "regex", ignore = "the `regex` feature is enabled")]
+fn regex_redaction() {
+    let result = fn_fixture_lib::make_snapshots(
+        &r#""snapshot-tests", redact(regex("[0-9]+", "[number]"))"#.parse().unwrap(),
+        &"fn numbered(text: &str) -> &str { text }".parse().unwrap(),
+    );
+    let err = result.expect_err("Expected a missing feature");
+    assert_eq!(
+        err.to_string(),
+        r#"compile_error ! { "`regex` redaction requires the `regex` feature of fn-fixture" }"#,
+    );
+}
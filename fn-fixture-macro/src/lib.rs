@@ -0,0 +1,19 @@
+//! This crate exists only because a procedural macro crate cannot
+//! export anything besides its macros. Use [`fn-fixture`] instead,
+//! which re-exports the macro alongside the runtime it depends on.
+//!
+//! [`fn-fixture`]: https://docs.rs/fn-fixture/
+
+extern crate proc_macro;
+
+/// Denotes the entrance point of a function-fixture's snapshots.
+#[proc_macro_attribute]
+pub fn snapshot(path_attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match fn_fixture_lib::make_snapshots(
+        &path_attr.into(),
+        &item.into(),
+    ) {
+        Ok(value) => value,
+        Err(value) => value,
+    }.into()
+}
@@ -0,0 +1,4 @@
+(
+    "Ok(\n    3,\n)\n--- tokens ---\n[\n    \"1\",\n]\n",
+    "Ok(\n    3,\n)\n--- tokens ---\n[\n    \"1\",\n]\n",
+)
@@ -0,0 +1,4 @@
+(
+    "Ok(\n    3,\n)\n--- tokens ---\n[]\n",
+    "Ok(\n    4,\n)\n--- stack ---\n[]\n",
+)
@@ -0,0 +1,4 @@
+(
+    r#"{ "items": [{ "price": 1.5 }, { "price": 2 }, {}], "removed": true, "odd key": null }"#,
+    r#"{ "items": [{ "price": 1.50001 }, { "price": "2" }], "added": [], "odd key": 0 }"#,
+)
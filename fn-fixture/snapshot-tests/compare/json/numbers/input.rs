@@ -0,0 +1,4 @@
+(
+    r#"{ "whole": 4, "fraction": 2.50, "large": 18446744073709551615 }"#,
+    r#"{ "whole": 4.0, "fraction": 2.5, "large": 18446744073709551615 }"#,
+)
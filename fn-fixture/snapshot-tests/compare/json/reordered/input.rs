@@ -0,0 +1,4 @@
+(
+    r#"{ "items": [1, 2], "name": "list" }"#,
+    r#"{ "name": "list", "items": [1, 2] }"#,
+)
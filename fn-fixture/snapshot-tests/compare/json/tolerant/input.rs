@@ -0,0 +1,4 @@
+(
+    r#"{ "items": [{ "price": 1.5 }, { "price": 2.0 }] }"#,
+    r#"{ "items": [{ "price": 1.5000000001 }, { "price": 2.01 }] }"#,
+)
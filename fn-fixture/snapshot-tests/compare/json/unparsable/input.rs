@@ -0,0 +1,4 @@
+(
+    r#"{ "items": [] }"#,
+    r#"{ "items": [ }"#,
+)
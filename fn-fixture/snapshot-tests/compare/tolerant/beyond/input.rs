@@ -0,0 +1,4 @@
+(
+    "Ok(\n    [\n        0.3,\n        1.5,\n        u8,\n    ],\n)\n",
+    "Ok(\n    [\n        0.3,\n        1.50001,\n        u16,\n    ],\n)\n",
+)
@@ -0,0 +1,4 @@
+(
+    "Ok(\n    [\n        0.3,\n    ],\n)\n",
+    "Ok(\n    [\n        0.3,\n        0.4,\n    ],\n)",
+)
@@ -0,0 +1,4 @@
+(
+    "Ok(\n    [\n        0.30000000000000004,\n        -1e-10,\n        Point2 {\n            x: 1.5,\n        },\n    ],\n)\n",
+    "Ok(\n    [\n        0.3,\n        0.0,\n        Point2 {\n            x: 1.5000000001,\n        },\n    ],\n)\n",
+)
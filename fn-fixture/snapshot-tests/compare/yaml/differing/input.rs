@@ -0,0 +1,4 @@
+(
+    "Ok:\n  answer: 42\n  question: []\n",
+    "Ok:\n  question: [life]\n  answer: 42.0\n",
+)
@@ -0,0 +1,3 @@
+pub struct Group {
+    pub title: String,
+}
@@ -0,0 +1,4 @@
+pub struct User {
+    pub name: String,
+    pub email: String,
+}
@@ -0,0 +1 @@
+Box::new(true)
\ No newline at end of file
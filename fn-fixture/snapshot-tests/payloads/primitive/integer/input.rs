@@ -0,0 +1 @@
+Box::new(42u8)
\ No newline at end of file
@@ -0,0 +1 @@
+Box::new(crate::PayloadError { code: 7 })
\ No newline at end of file
@@ -0,0 +1 @@
+Box::new(std::io::Error::new(std::io::ErrorKind::NotFound, "settings.toml"))
\ No newline at end of file
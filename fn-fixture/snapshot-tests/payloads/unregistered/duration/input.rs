@@ -0,0 +1 @@
+Box::new(std::time::Duration::from_millis(5))
\ No newline at end of file
@@ -0,0 +1,3 @@
+(0..12)
+    .map(|number| (number, format!("item{}", number)))
+    .collect::<std::collections::HashMap<_, _>>()
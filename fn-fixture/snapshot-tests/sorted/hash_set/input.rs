@@ -0,0 +1,3 @@
+["pear", "apple", "fig", "banana", "cherry", "date", "elderberry", "grape"]
+    .iter()
+    .collect::<std::collections::HashSet<_>>()
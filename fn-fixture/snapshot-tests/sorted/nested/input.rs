@@ -0,0 +1,27 @@
+{
+    use std::collections::{
+        HashMap,
+        HashSet,
+    };
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Inventory {
+        // Struct fields keep their declared order
+        name: &'static str,
+        aisles: HashMap<&'static str, HashSet<u32>>,
+        // Lists keep their order
+        history: Vec<u32>,
+    }
+
+    let mut aisles = HashMap::new();
+    aisles.insert("produce", (1..6).collect());
+    aisles.insert("bakery", [30, 4, 200].iter().copied().collect());
+    aisles.insert("dairy", HashSet::new());
+
+    Inventory {
+        name: "corner store",
+        aisles,
+        history: vec![3, 1, 2],
+    }
+}
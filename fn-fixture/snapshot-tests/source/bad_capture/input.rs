@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/output\", capture(stdin)",
+    "fn greet(name: &str) -> usize { println!(\"Hello, {}!\", name); name.len() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/bad_cfg\"",
+    "fn split_words(input: &str) -> Vec<String> { unimplemented!() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/code\", compare = \"structural\"",
+    "fn do_thing<T>(input: T) -> T { input }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/configured\", capture(stdout)",
+    "fn tally(input: &str) -> HashMap<String, usize> { unimplemented!() }",
+)
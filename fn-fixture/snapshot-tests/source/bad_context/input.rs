@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/context\"",
+    "fn locate(input: &str, context: &Context) -> usize { input.len() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/determinism\", determinism = 1",
+    "fn count_words(text: &str) -> HashMap<&str, usize> { unimplemented!() }",
+)
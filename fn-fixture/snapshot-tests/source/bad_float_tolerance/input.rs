@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/examples\", compare = \"exact\", float_tolerance = 1e-9",
+    "fn parse_float(value: &str) -> Result<f64, impl std::fmt::Debug> { value.parse() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/timeouts\", timeout = \"307445734561825861m\"",
+    "fn sleep_for(millis: &str) -> u64 { unimplemented!() }",
+)
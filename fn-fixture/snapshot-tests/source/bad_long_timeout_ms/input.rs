@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/timeouts\", timeout = \"18446744073709551616ms\"",
+    "fn sleep_for(millis: &str) -> u64 { unimplemented!() }",
+)
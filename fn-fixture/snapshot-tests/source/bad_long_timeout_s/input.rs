@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/timeouts\", timeout = \"18446744073709552s\"",
+    "fn sleep_for(millis: &str) -> u64 { unimplemented!() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/mixed_inputs\"",
+    "fn files(root: &std::path::Path) -> bool { root.exists() }",
+)
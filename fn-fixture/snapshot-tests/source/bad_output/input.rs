@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/structured\", output = \"xml\"",
+    "fn to_xml<T>(input: T) -> T { input }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/payloads\", panic_payloads(\"std::io::Error\")",
+    "fn raise(payload: Box<std::any::Any + Send>) { std::panic::resume_unwind(payload) }",
+)
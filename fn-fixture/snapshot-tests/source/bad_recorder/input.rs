@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/recorded\"",
+    "fn evaluate(program: &str, snap: &fn_fixture::Recorder) -> usize { program.len() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/redact\", redact(passwords)",
+    "fn redacted(text: &str) -> Vec<&str> { text.lines().collect() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/redact\", redact(regex(\"[0-9+ms\", \"[duration]\"))",
+    "fn redacted(text: &str) -> Vec<&str> { text.lines().collect() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/timeouts\", timeout = \"5 seconds\"",
+    "fn sleep_for(millis: &str) -> u64 { unimplemented!() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/variants\", variants(\"unix\")",
+    "fn parse_size(input: &str) -> Result<usize, std::num::ParseIntError> { unimplemented!() }",
+)
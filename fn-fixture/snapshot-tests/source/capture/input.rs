@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/output\", capture(stdout, stderr)",
+    "fn greet(name: &str) -> usize { println!(\"Hello, {}!\", name); name.len() }",
+)
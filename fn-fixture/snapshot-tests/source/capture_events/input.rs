@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/events\", capture(stdout, log, tracing)",
+    "fn parse_level(value: &str) -> Option<u8> { value.parse().ok() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/context\"",
+    "fn locate(input: &str, context: &fn_fixture::Context, snap: &mut fn_fixture::Recorder) -> usize { snap.record(\"name\", context.name()); input.len() }",
+)
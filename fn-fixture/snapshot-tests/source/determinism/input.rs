@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/determinism\", determinism = 3, render(sorted)",
+    "fn count_words(text: &str) -> HashMap<&str, usize> { unimplemented!() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/errors\", render(sorted, error_chain)",
+    "fn parse_settings(value: &str) -> Result<Vec<u32>, String> { unimplemented!() }",
+)
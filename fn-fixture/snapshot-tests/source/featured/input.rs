@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/featured\", output = \"json\"",
+    "fn split_words(input: &str) -> Vec<String> { unimplemented!() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/ignored\", strict",
+    "fn shout(input: &str) -> String { input.to_uppercase() }",
+)
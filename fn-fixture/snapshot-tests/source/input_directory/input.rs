@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/directories\"",
+    "fn bundle(root: &std::path::Path) -> usize { std::fs::read_dir(root).unwrap().count() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/isolated\", isolate",
+    "fn terminate(how: &str) -> u32 { std::process::exit(3) }",
+)
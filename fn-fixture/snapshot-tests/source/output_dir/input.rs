@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/generated\"",
+    "fn generate(schema: &str, out: &fn_fixture::OutputDir) -> usize { std::fs::write(out.join(\"schema\"), schema).unwrap(); schema.len() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/generated\", determinism = 2, timeout = \"5s\"",
+    "fn generate(schema: &str, out: &fn_fixture::OutputDir) -> usize { std::fs::write(out.join(\"schema\"), schema).unwrap(); schema.len() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/panic_details\", panic_details(location, backtrace)",
+    "fn parse_port(value: &str) -> u16 { value.trim().parse().expect(\"Not a number\") }",
+)
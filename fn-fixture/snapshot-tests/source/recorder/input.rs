@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/recorded\"",
+    "fn evaluate(program: &str, snap: &mut fn_fixture::Recorder) -> usize { snap.record(\"program\", program); program.len() }",
+)
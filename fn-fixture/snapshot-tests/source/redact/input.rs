@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/redact\", redact(manifest_dir, path_separators, addresses, uuids, timestamps), redact(regex(\"[0-9]+ms\", \"[duration]\"), with(hide_passwords))",
+    "fn redacted(text: &str) -> Vec<&str> { text.lines().collect() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/resources\", resources(\"include/**/*.h\", \"*.json\")",
+    "fn preprocess(source: &str, context: &fn_fixture::Context) -> usize { context.resources().len() }",
+)
@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/sorted\", render(sorted)",
+    "fn sorted<T: std::fmt::Debug>(input: T) -> T { input }",
+)
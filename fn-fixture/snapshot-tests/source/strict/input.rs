@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/strict\", strict(\"**/README.md\")",
+    "fn check(input: &str) -> usize { input.len() }",
+)
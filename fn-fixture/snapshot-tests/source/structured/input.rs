@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/structured\", output = \"json\"",
+    "fn to_json<T: serde::Serialize>(input: T) -> T { input }",
+)
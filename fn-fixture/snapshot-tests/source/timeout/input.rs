@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/timeouts\", timeout = \"500ms\", timeout(patient = \"1m\")",
+    "fn sleep_for(millis: &str) -> u64 { unimplemented!() }",
+)
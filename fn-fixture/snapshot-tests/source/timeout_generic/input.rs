@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/timeouts\", timeout = \"10s\"",
+    "fn waited_for<T: std::fmt::Debug>(millis: T) -> T where T: Clone { millis }",
+)
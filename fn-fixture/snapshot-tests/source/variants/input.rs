@@ -0,0 +1,4 @@
+(
+    "\"snapshot-tests/variants\", variants(target_pointer_width = \"32\", windows)",
+    "fn parse_size(input: &str) -> Result<usize, std::num::ParseIntError> { unimplemented!() }",
+)
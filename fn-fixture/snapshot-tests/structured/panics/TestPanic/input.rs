@@ -0,0 +1 @@
+(None as Option<u32>).expect("It's a PANIC!!")
\ No newline at end of file
@@ -0,0 +1,6 @@
+{
+    let mut map = std::collections::BTreeMap::new();
+    map.insert("answer", vec![4.0, 2.5]);
+    map.insert("question", vec![]);
+    map
+}
@@ -14,12 +14,15 @@
 //! * It must use a string literal containing the path (relative to
 //!   cargo manifest) to a base folder. A base folder must contain
 //!   test folders or base folders. Test folders must contain
-//!   exactly one of `input.rs`, `input.txt`, or `input.bin`.
+//!   exactly one of `input.rs`, `input.txt`, `input.bin`, or an
+//!   `input/` directory.
 //!
 //! * It must have a single parameter of the corresponding to a
 //!   respective type of the input files as included by their
 //!   respective macros, `include`, `include_str`, and
-//!   `include_bytes`.
+//!   `include_bytes`, or `&Path` to a copy of an `input/` directory.
+//!   It may be followed by a [`Recorder`], a [`Context`], or an
+//!   [`OutputDir`].
 //!
 //! [`std::fmt::Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
 //!
@@ -42,17 +45,123 @@
 //!
 //! Notice that `snapshot-tests/examples` does not itself contain an `input.txt`
 //!
+//! Files and folders starting with `.` are skipped, as are those
+//! matching the `.gitignore`-style patterns of a `.fixtureignore` in
+//! their folder or above.
+//!
+//! # Options
+//!
+//! Options follow the path in the attribute:
+//!
+//! * `output = "debug"` (the default) stores `{:#?}` of the result in
+//!   `<name>.txt`. With the `serde` feature, `output = "json"`,
+//!   `output = "yaml"`, and `output = "ron"` instead store the
+//!   [`serde::Serialize`] form of the result in `<name>.json`,
+//!   `<name>.yaml`, and `<name>.ron`.
+//!
+//! * `compare = "structural"` (the default for `json` and `yaml`)
+//!   ignores the order of object keys and compares numbers by value.
+//!   `compare = "exact"` (the default otherwise) compares the text.
+//!
+//! * `float_tolerance = 1e-9` considers numbers within the tolerance
+//!   of each other to be equal, both in the text and in structurally
+//!   compared documents.
+//!
+//! * `render(sorted)` sorts the entries of maps and sets in the
+//!   `{:#?}` rendering, so that `HashMap` and `HashSet` render the
+//!   same every run.
+//!
+//! * `render(error_chain)` renders the `Err` of a `Result` as its
+//!   message and the messages of its `source()` chain. The error must
+//!   convert into a `Box<dyn Error>`.
+//!
+//! * `redact(...)` normalizes the rendered result before it is
+//!   compared or written, using any of `manifest_dir`,
+//!   `path_separators`, `addresses`, `uuids`, `timestamps`,
+//!   `regex("pattern", "replacement")` (with the `regex` feature), or
+//!   `with(function)` for a `fn(&str) -> String`.
+//!
+//! * `panic_payloads(std::io::Error, MyError)` renders panics with a
+//!   payload of those types as the path as written wrapping its `{:?}`.
+//!   Primitive payloads are recognized without being listed, and any
+//!   other payload is rendered as `Unregistered(..)`.
+//!
+//! * `panic_details(location, backtrace)` records where the fixture
+//!   panicked in sections after the result: the file, line, and column
+//!   relative to the crate, and the names of the crate's functions in
+//!   the backtrace. Either may be given alone.
+//!
+//! * `isolate` runs each fixture in a child process of the test binary,
+//!   recording the exit code or signal and the end of stderr when the
+//!   child aborts, overflows its stack, or exits.
+//!
+//! * `timeout = "5s"` records a `Timeout` result for a fixture that runs
+//!   longer, and `timeout(folder::path = "1m")` overrides it for the
+//!   tests in a folder.
+//!
+//! * `determinism = 3` calls the fixture 3 times with fresh inputs, and
+//!   fails with the differences if the renderings disagree.
+//!
+//! * `capture(stdout, stderr)` records what the fixture writes to
+//!   stdout and stderr in sections after the result. Capturing implies
+//!   `isolate`, and is only supported on Unix. With the `log` and
+//!   `tracing` features, `capture(log, tracing)` records the fixture's
+//!   `log` records and `tracing` events in sections as well.
+//!
+//! * `resources("include/**/*.h", "*.json")` accepts files matching the
+//!   globs beside each input, embedding them for
+//!   [`Context::resources`].
+//!
+//! * `strict` fails on files in the tree that no fixture of the crate
+//!   reads or writes, and `strict("**/README.md")` allows others by
+//!   glob.
+//!
+//! * `variants(target_pointer_width = "32", unix)` compares to
+//!   `<name>.target_pointer_width_32.txt` or `<name>.unix.txt`, when
+//!   present as the test runs and the `cfg!` holds, before
+//!   `<name>.txt`. With `strict`, each variant needs a file somewhere.
+//!
+//! # Recorded sections
+//!
+//! A fixture may take a [`Recorder`] after its input, as in
+//! `fn compile(src: &str, snap: &mut fn_fixture::Recorder)`. Each
+//! `snap.record("tokens", &tokens)` stores `{:#?}` of the value in a
+//! section named `tokens` after the result, compared on its own.
+//!
+//! # Output directories
+//!
+//! A fixture may take an [`OutputDir`] after its input, to write files
+//! that are compared to the `<name>.out/` tree beside its expected
+//! file. On a difference, the files are written to
+//! `<name>.actual.out/`.
+//!
+//! # Context
+//!
+//! A fixture may take a [`Context`] after its input, as in
+//! `fn load(src: &str, context: &fn_fixture::Context)`, to find its
+//! directory, test path, and name, and files next to its input.
+//!
+//! # Folder settings
+//!
+//! A `fixture.toml` sets `ignore`, `xfail`, `panics`, `timeout`,
+//! `render`, `tags`, and `cfg` for the tests of its folder and every
+//! folder below, with a nearer file overriding a farther one. An
+//! `input.ignore` or `input.xfail` beside an input marks that test
+//! alone, with its contents as the reason.
+//!
+//! A test marked `xfail` passes while its result differs from the
+//! expected file, and fails once it matches.
+//!
+//! [`serde::Serialize`]: https://docs.rs/serde/1/serde/trait.Serialize.html
+//!
+
+pub use fn_fixture_macro::snapshot;
 
-extern crate proc_macro;
+pub use runtime::{
+    context::Context,
+    output_dir::OutputDir,
+    sections::Recorder,
+};
 
-/// Denotes the entrance point of a function-fixture's snapshots.
-#[proc_macro_attribute]
-pub fn snapshot(path_attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    match fn_fixture_lib::make_snapshots(
-        &path_attr.into(),
-        &item.into(),
-    ) {
-        Ok(value) => value,
-        Err(value) => value,
-    }.into()
-}
+#[doc(hidden)]
+pub mod runtime;
@@ -0,0 +1,402 @@
+//! Support code called by the tests that [`snapshot`] generates.
+//!
+//! Nothing here is intended to be called directly; it is public so
+//! that generated code can reach it through `fn_fixture::runtime`.
+//!
+//! [`snapshot`]: crate::snapshot
+
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::RefCell,
+    fmt::{
+        self,
+        Debug,
+    },
+    panic::{
+        catch_unwind,
+        UnwindSafe,
+    },
+    path::Path,
+    sync::Once,
+};
+
+pub mod compare;
+pub mod context;
+pub mod determinism;
+pub mod events;
+pub mod input;
+pub mod isolate;
+pub mod output;
+pub mod output_dir;
+pub mod redact;
+pub mod render;
+pub mod sections;
+pub mod timeout;
+
+use self::compare::Comparator;
+
+/// The panic half of a fixture's outcome.
+///
+/// `Ok` holds a `String` or `&str` payload, `Err` holds anything else.
+pub type Panic = Result<(&'static str, Box<String>), (&'static str, Payload)>;
+
+/// The outcome of calling a fixture, panics included.
+pub type Outcome<R> = Result<R, Panic>;
+
+/// A fixture that ended without an outcome.
+///
+/// Rendered in place of the outcome, in the fixture's output format.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Interrupted {
+    /// The isolated child process ended without a result, with its
+    /// exit code or signal and the last lines of its stderr.
+    Died {
+        status: String,
+        stderr: Vec<String>,
+    },
+    /// The fixture ran past its timeout.
+    Timeout {
+        after: String,
+    },
+}
+
+/// Recognizes a panic payload of one type, giving its value.
+pub type Downcast = for<'a> fn(&'a (dyn Any + Send)) -> Option<&'a dyn Debug>;
+
+/// The [`Downcast`] of `T`, as registered by `panic_payloads(T)`.
+pub fn payload<T: Any + Debug>(payload: &(dyn Any + Send)) -> Option<&dyn Debug> {
+    payload
+        .downcast_ref::<T>()
+        .map(|value| value as &dyn Debug)
+}
+
+/// Payloads recognized without being registered, by the name they
+/// render under.
+const PRIMITIVES: &[(&str, Downcast)] = &[
+    ("i8", payload::<i8>), ("i16", payload::<i16>), ("i32", payload::<i32>),
+    ("i64", payload::<i64>), ("i128", payload::<i128>), ("isize", payload::<isize>),
+    ("u8", payload::<u8>), ("u16", payload::<u16>), ("u32", payload::<u32>),
+    ("u64", payload::<u64>), ("u128", payload::<u128>), ("usize", payload::<usize>),
+    ("f32", payload::<f32>), ("f64", payload::<f64>), ("bool", payload::<bool>),
+    ("char", payload::<char>), ("()", payload::<()>),
+];
+
+/// Stands in for the value of a payload that is neither registered
+/// nor primitive, as the type behind a `dyn Any` cannot be named.
+struct Unregistered;
+
+impl Debug for Unregistered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<type not in panic_payloads(...)>")
+    }
+}
+
+/// A panic payload that is not a `String` or `&str`.
+///
+/// Renders as `Name(value)` when its type is primitive or registered,
+/// under the path written in `panic_payloads(...)`, and otherwise as
+/// `Unregistered(<type not in panic_payloads(...)>)`.
+pub struct Payload {
+    payload: Box<dyn Any + Send>,
+    registry: &'static [(&'static str, Downcast)],
+}
+
+impl Payload {
+    /// The name of the payload's type and its value, if recognized.
+    pub fn describe(&self) -> Option<(&'static str, &dyn Debug)> {
+        self.registry
+            .iter()
+            .chain(PRIMITIVES)
+            .find_map(|&(name, downcast)| Some((name, downcast(&*self.payload)?)))
+    }
+}
+
+impl Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, value) = self.describe().unwrap_or(("Unregistered", &Unregistered));
+        f.debug_tuple(name).field(value).finish()
+    }
+}
+
+/// The outcome of calling a fixture, with what the panic hook saw of
+/// its panic.
+pub struct Caught<R> {
+    pub outcome: Outcome<R>,
+    pub panic: Option<PanicDetails>,
+}
+
+/// Where a fixture panicked, as seen by the panic hook.
+///
+/// Panics that do not go through the hook, like
+/// [`std::panic::resume_unwind`], have no details.
+pub struct PanicDetails {
+    /// The panic's message, or `Box<dyn Any>` without one.
+    pub message: String,
+    /// File, line, and column, with the file as given by `file!()`.
+    pub location: Option<(String, u32, u32)>,
+    /// The unfiltered backtrace, when requested.
+    pub backtrace: Option<String>,
+}
+
+impl fmt::Display for PanicDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some((file, line, column)) => write!(f, "Fixture panicked at {}:{}:{}:\n{}", file, line, column, self.message),
+            None => write!(f, "Fixture panicked:\n{}", self.message),
+        }
+    }
+}
+
+/// What the panic hook records for the fixture running on this thread.
+struct Capture {
+    backtrace: bool,
+    panic: Option<PanicDetails>,
+}
+
+thread_local! {
+    static CAPTURE: RefCell<Option<Capture>> = const { RefCell::new(None) };
+}
+
+/// Wraps the current panic hook, once, with one that quietly records
+/// the details of panics from a fixture.
+///
+/// Other panics, like those of a failed comparison, still go to the
+/// previous hook.
+fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let captured = CAPTURE.with(|capture| {
+                if let Some(capture) = capture.borrow_mut().as_mut() {
+                    let payload = info.payload();
+                    capture.panic = Some(PanicDetails {
+                        message: payload
+                            .downcast_ref::<&str>()
+                            .map(|message| message.to_string())
+                            .or_else(|| payload.downcast_ref::<String>().cloned())
+                            .unwrap_or_else(|| "Box<dyn Any>".to_string()),
+                        location: info
+                            .location()
+                            .map(|location| (location.file().to_string(), location.line(), location.column())),
+                        backtrace: if capture.backtrace {
+                            Some(Backtrace::force_capture().to_string())
+                        } else {
+                            None
+                        },
+                    });
+                    true
+                } else {
+                    false
+                }
+            });
+            if !captured {
+                previous(info)
+            }
+        }));
+    });
+}
+
+/// Calls the fixture, recording a panic instead of propagating it.
+///
+/// Payloads of the types in `registry` are kept for rendering, and a
+/// backtrace of the panic is kept when `backtrace` is set.
+pub fn catch<R>(registry: &'static [(&'static str, Downcast)], backtrace: bool, call: impl FnOnce() -> R + UnwindSafe) -> Caught<R> {
+    install_hook();
+    let outer = CAPTURE.with(|capture| capture.replace(Some(Capture {
+        backtrace,
+        panic: None,
+    })));
+    // <String> panics come from the formatted panic!, including .unwrap/.expect
+    // <&str> panics come from unformatted panic!, like panic!("Nooo!")
+    let outcome = catch_unwind(call).map_err(|err| err
+        .downcast::<String>()
+        .or_else(|err|
+            if let Some(string) = err.downcast_ref::<&str>() {
+                Ok(Box::new(string.to_string()))
+            } else {
+                Err(("<!String> Panic", Payload { payload: err, registry }))
+            }
+        )
+        .map(|ok| ("<String> Panic", ok))
+    );
+    let panic = CAPTURE
+        .with(|capture| capture.replace(outer))
+        .and_then(|capture| capture.panic)
+        .filter(|_| outcome.is_err());
+    Caught {
+        outcome,
+        panic,
+    }
+}
+
+/// The expected file of a test: the first of `variants` whose
+/// predicate holds and whose file exists, otherwise `expected_file`.
+///
+/// Files are looked for as the test runs, so a new variant needs no
+/// rebuild. A variant whose predicate holds but whose file is missing
+/// is noted on stderr, which shows when the test fails.
+pub fn variant(expected_file: &'static str, variants: &[(bool, &'static str)]) -> &'static str {
+    let mut missing = Vec::new();
+    let chosen = variants
+        .iter()
+        .filter(|&&(holds, _)| holds)
+        .map(|&(_, variant)| variant)
+        .find(|variant| Path::new(variant).is_file() || {
+            missing.push(*variant);
+            false
+        })
+        .unwrap_or(expected_file);
+    for variant in missing {
+        eprintln!("note: no {}, though its predicate holds, so comparing to {}", variant, chosen);
+    }
+    chosen
+}
+
+/// Compares the rendered result to the expected file, or writes the
+/// actual file when there is nothing to compare to.
+///
+/// An exact `comparator` checks the result before its [`sections`]
+/// with `assert_eq!`, and each section exactly, reporting the lines
+/// that differ in it. Otherwise, the result is compared by
+/// `comparator`, and each section line-by-line. When any fails, the
+/// fixture's panic, if any, is shown with the failure.
+#[track_caller]
+pub fn check(result: &str, panic: &Option<PanicDetails>, expected_file: &str, actual_file: &str, comparator: &dyn Comparator) {
+    let panic = panic
+        .as_ref()
+        .map_or_else(String::new, |panic| format!("\n\n{}", panic));
+    if Path::new(expected_file).is_file() {
+        let expected = std::fs::read_to_string(expected_file)
+            .unwrap_or_else(|err|
+                panic!("Reading expected from {}: {:?}", expected_file, err)
+            );
+        if comparator.is_exact() {
+            let (result, actual_sections) = sections::split(result);
+            let (expected, expected_sections) = sections::split(&expected);
+            let reports = compare_sections(&expected_sections, &actual_sections, |expected, actual| compare::Lines
+                .compare(expected, actual)
+                .and_then(|()| compare::Exact.compare(expected, actual))
+            );
+            let sections = reports
+                .iter()
+                .map(|report| format!("\n{}", report))
+                .collect::<String>();
+            assert_eq!(result, expected, "Result differs from {}{}{}", expected_file, sections, panic);
+            if !reports.is_empty() {
+                panic!("Result differs from {}{}{}", expected_file, sections, panic)
+            }
+        } else if let Err(report) = compare(comparator, &expected, result) {
+            panic!("Result differs from {}\n{}{}", expected_file, report, panic)
+        }
+    } else {
+        std::fs::write(actual_file, result.as_bytes())
+            .unwrap_or_else(|err|
+                panic!("Writing actual to {}: {:?}", actual_file, err)
+            );
+        panic!("No expected value set: {}{}", actual_file, panic)
+    }
+}
+
+fn compare(comparator: &dyn Comparator, expected: &str, actual: &str) -> Result<(), String> {
+    let (expected, expected_sections) = sections::split(expected);
+    let (actual, actual_sections) = sections::split(actual);
+    let mut reports = Vec::new();
+    if let Err(report) = comparator.compare(expected, actual) {
+        reports.push(report);
+    }
+    reports.extend(compare_sections(&expected_sections, &actual_sections, |expected, actual| compare::Lines
+        .compare(expected, actual)
+    ));
+    if reports.is_empty() {
+        Ok(())
+    } else {
+        Err(reports.join("\n"))
+    }
+}
+
+/// A report of each section that differs by `compare`, or of the
+/// names when they differ.
+fn compare_sections(
+    expected_sections: &[(&str, &str)],
+    actual_sections: &[(&str, &str)],
+    compare: impl Fn(&str, &str) -> Result<(), String>,
+) -> Vec<String> {
+    let expected_names: Vec<&str> = expected_sections.iter().map(|&(name, _)| name).collect();
+    let actual_names: Vec<&str> = actual_sections.iter().map(|&(name, _)| name).collect();
+    if expected_names != actual_names {
+        return vec![format!(
+            "Sections differ:\n  left: {:?}\n right: {:?}",
+            actual_names,
+            expected_names,
+        )]
+    }
+    expected_sections
+        .iter()
+        .zip(actual_sections)
+        .filter_map(|(&(name, expected), &(_, actual))| compare(expected, actual)
+            .err()
+            .map(|report| format!("Section {}:\n{}", name, report))
+        )
+        .collect()
+}
+
+/// Fails if the fixture was `expected` to panic and did not, or was
+/// expected not to and did.
+#[track_caller]
+pub fn expect_panic(expected: Option<bool>, panic: &Option<PanicDetails>) {
+    match (expected, panic) {
+        (Some(true), None) => panic!("Expected the fixture to panic"),
+        (Some(false), Some(panic)) => panic!("Expected the fixture not to panic\n\n{}", panic),
+        _ => {},
+    }
+}
+
+/// Runs the `check` of a known failure, with its `reason`, failing if
+/// it passes so that a fix is noticed.
+///
+/// Without an expected file there is nothing known to differ from, so
+/// the check fails as usual.
+#[track_caller]
+pub fn expect_failure(reason: Option<&str>, expected_file: &str, check: impl FnOnce() + UnwindSafe) {
+    let reason = match reason {
+        Some(reason) if Path::new(expected_file).is_file() => reason,
+        _ => return check(),
+    };
+    if catch_unwind(check).is_ok() {
+        if reason.is_empty() {
+            panic!("Expected a failure, but the result matches {}; remove its xfail", expected_file)
+        } else {
+            panic!("Expected a failure ({}), but the result matches {}; remove its xfail", reason, expected_file)
+        }
+    }
+}
+
+/// The value for the test at `test_path` of the deepest folder in
+/// `overrides` containing the test, otherwise `default`.
+///
+/// The folders are relative to the module at `prefix`, which is the
+/// module of the fixture's tests, and the empty folder contains every
+/// test.
+pub fn select<T: Clone>(test_path: &str, prefix: &str, default: Option<T>, overrides: &[(&str, T)]) -> Option<T> {
+    let relative = relative(test_path, prefix);
+    overrides
+        .iter()
+        .filter(|(folder, _)| folder.is_empty() || relative
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        )
+        .max_by_key(|(folder, _)| folder.len())
+        .map(|(_, value)| value.clone())
+        .or(default)
+}
+
+/// The path of the test at `test_path` from the module at `prefix`.
+fn relative<'a>(test_path: &'a str, prefix: &str) -> &'a str {
+    test_path
+        .strip_prefix(prefix)
+        .and_then(|relative| relative.strip_prefix("::"))
+        .unwrap_or(test_path)
+}
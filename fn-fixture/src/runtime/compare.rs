@@ -0,0 +1,286 @@
+//! Decides whether a rendered result matches its expected file.
+
+/// Compares the contents of an expected file to a freshly rendered result.
+pub trait Comparator {
+    /// Describes the differences, unless the two are considered equal.
+    fn compare(&self, expected: &str, actual: &str) -> Result<(), String>;
+
+    /// Whether this is plain equality, which is checked with `assert_eq!`
+    /// so that IDEs and test runners can offer a diff.
+    fn is_exact(&self) -> bool {
+        false
+    }
+}
+
+/// Character-for-character equality of the rendered text.
+pub struct Exact;
+
+impl Comparator for Exact {
+    fn compare(&self, expected: &str, actual: &str) -> Result<(), String> {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(format!("assertion `left == right` failed\n  left: {:?}\n right: {:?}", actual, expected))
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// Equality of the rendered text, except that numbers within
+/// `float_tolerance` of each other are considered equal.
+///
+/// Lines are compared one-by-one, and only the lines that differ
+/// are reported.
+pub struct Tolerant {
+    pub float_tolerance: f64,
+}
+
+impl Comparator for Tolerant {
+    fn compare(&self, expected: &str, actual: &str) -> Result<(), String> {
+        line_differences(expected, actual, |expected, actual| {
+            let (expected_text, expected_numbers) = split_numbers(expected);
+            let (actual_text, actual_numbers) = split_numbers(actual);
+            expected_text == actual_text
+                && expected_numbers
+                    .iter()
+                    .zip(&actual_numbers)
+                    .all(|(&expected, &actual)| within(self.float_tolerance, expected, actual))
+        })
+    }
+}
+
+/// Character-for-character equality, like [`Exact`], except that only
+/// the lines that differ are reported.
+pub struct Lines;
+
+impl Comparator for Lines {
+    fn compare(&self, expected: &str, actual: &str) -> Result<(), String> {
+        line_differences(expected, actual, |expected, actual| expected == actual)
+    }
+}
+
+/// Compares the text line-by-line, reporting the lines that are not `equal`.
+fn line_differences(expected: &str, actual: &str, equal: impl Fn(&str, &str) -> bool) -> Result<(), String> {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut report = Vec::new();
+    for line in 1.. {
+        let (expected, actual) = match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => break,
+            lines => lines,
+        };
+        let equal = match (expected, actual) {
+            (Some(expected), Some(actual)) => equal(expected, actual),
+            _ => false,
+        };
+        if !equal {
+            let quoted = |line: Option<&str>| line
+                .map_or_else(|| "missing".to_string(), |line| format!("{:?}", line));
+            report.push(format!(
+                "line {}: {} != {}",
+                line,
+                quoted(expected),
+                quoted(actual),
+            ));
+        }
+    }
+    if expected.ends_with('\n') != actual.ends_with('\n') {
+        report.push("Differing newline at end of file".to_string());
+    }
+    if report.is_empty() {
+        Ok(())
+    } else {
+        report.insert(0, "Differences (expected != actual):".to_string());
+        Err(report.join("\n"))
+    }
+}
+
+fn within(tolerance: f64, expected: f64, actual: f64) -> bool {
+    expected == actual || (expected - actual).abs() <= tolerance
+}
+
+/// Separates the numbers in a line from the text around them.
+///
+/// A number only starts where an identifier could not continue, so
+/// the `8` in `u8` or `Vec2` stays part of the text.
+fn split_numbers(line: &str) -> (String, Vec<f64>) {
+    let bytes = line.as_bytes();
+    let mut text = String::new();
+    let mut numbers = Vec::new();
+    let mut index = 0;
+    let mut start = 0;
+    while index < bytes.len() {
+        let boundary = index == 0 || {
+            let previous = bytes[index - 1];
+            !(previous.is_ascii_alphanumeric() || previous == b'_' || previous == b'.')
+        };
+        let digit_at = |at: usize| bytes.get(at).is_some_and(u8::is_ascii_digit);
+        let number_start = boundary && (
+            digit_at(index) || (bytes[index] == b'-' && digit_at(index + 1))
+        );
+        if !number_start {
+            index += 1;
+            continue;
+        }
+        let mut end = index + 1;
+        while digit_at(end) {
+            end += 1;
+        }
+        if bytes.get(end) == Some(&b'.') && digit_at(end + 1) {
+            end += 1;
+            while digit_at(end) {
+                end += 1;
+            }
+        }
+        if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+            let sign = matches!(bytes.get(end + 1), Some(b'-') | Some(b'+')) as usize;
+            if digit_at(end + 1 + sign) {
+                end += 1 + sign;
+                while digit_at(end) {
+                    end += 1;
+                }
+            }
+        }
+        if let Ok(number) = line[index..end].parse() {
+            text.push_str(&line[start..index]);
+            text.push('#');
+            numbers.push(number);
+            start = end;
+        }
+        index = end;
+    }
+    text.push_str(&line[start..]);
+    (text, numbers)
+}
+
+#[cfg(feature = "serde")]
+pub use self::structural::*;
+
+#[cfg(feature = "serde")]
+mod structural {
+    use std::fmt::Display;
+
+    use serde_json::{
+        Number,
+        Value,
+    };
+
+    use super::Comparator;
+
+    /// Semantic equality of JSON documents.
+    ///
+    /// Object keys may be in any order and numbers are compared by
+    /// value, so `1.0` matches `1`. Numbers within `float_tolerance`
+    /// of each other are also considered equal.
+    #[derive(Default)]
+    pub struct Json {
+        pub float_tolerance: f64,
+    }
+
+    /// Semantic equality of YAML documents, the same as [`Json`].
+    #[derive(Default)]
+    pub struct Yaml {
+        pub float_tolerance: f64,
+    }
+
+    impl Comparator for Json {
+        fn compare(&self, expected: &str, actual: &str) -> Result<(), String> {
+            structural(
+                self.float_tolerance,
+                parse("expected", serde_json::from_str(expected))?,
+                parse("actual", serde_json::from_str(actual))?,
+            )
+        }
+    }
+
+    impl Comparator for Yaml {
+        fn compare(&self, expected: &str, actual: &str) -> Result<(), String> {
+            structural(
+                self.float_tolerance,
+                parse("expected", serde_yaml::from_str(expected))?,
+                parse("actual", serde_yaml::from_str(actual))?,
+            )
+        }
+    }
+
+    fn parse<E: Display>(which: &str, result: Result<Value, E>) -> Result<Value, String> {
+        result.map_err(|err| format!("Failed to parse {}: {}", which, err))
+    }
+
+    fn structural(tolerance: f64, expected: Value, actual: Value) -> Result<(), String> {
+        let mut report = Vec::new();
+        differences(tolerance, &mut "$".to_string(), Some(&expected), Some(&actual), &mut report);
+        if report.is_empty() {
+            Ok(())
+        } else {
+            report.insert(0, "Differences (expected != actual):".to_string());
+            Err(report.join("\n"))
+        }
+    }
+
+    fn differences(tolerance: f64, path: &mut String, expected: Option<&Value>, actual: Option<&Value>, report: &mut Vec<String>) {
+        let length = path.len();
+        match (expected, actual) {
+            (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+                for (key, value) in expected {
+                    push_key(path, key);
+                    differences(tolerance, path, Some(value), actual.get(key), report);
+                    path.truncate(length);
+                }
+                for (key, value) in actual {
+                    if !expected.contains_key(key) {
+                        push_key(path, key);
+                        differences(tolerance, path, None, Some(value), report);
+                        path.truncate(length);
+                    }
+                }
+            },
+            (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+                for index in 0..expected.len().max(actual.len()) {
+                    path.push_str(&format!("[{}]", index));
+                    differences(tolerance, path, expected.get(index), actual.get(index), report);
+                    path.truncate(length);
+                }
+            },
+            (Some(Value::Number(expected)), Some(Value::Number(actual)))
+            if numbers_equal(tolerance, expected, actual) => {},
+            (Some(expected), Some(actual)) if expected == actual => {},
+            (expected, actual) => report.push(format!(
+                "{}: {} != {}",
+                path,
+                expected.map_or_else(|| "missing".to_string(), Value::to_string),
+                actual.map_or_else(|| "missing".to_string(), Value::to_string),
+            )),
+        }
+    }
+
+    fn numbers_equal(tolerance: f64, left: &Number, right: &Number) -> bool {
+        if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+            return left == right
+        }
+        if let (Some(left), Some(right)) = (left.as_u64(), right.as_u64()) {
+            return left == right
+        }
+        match (left.as_f64(), right.as_f64()) {
+            (Some(left), Some(right)) => super::within(tolerance, left, right),
+            _ => false,
+        }
+    }
+
+    fn push_key(path: &mut String, key: &str) {
+        let plain = key
+            .chars()
+            .next()
+            .is_some_and(|first| first.is_alphabetic() || first == '_')
+            && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if plain {
+            path.push('.');
+            path.push_str(key);
+        } else {
+            path.push_str(&format!("[{}]", Value::from(key)));
+        }
+    }
+}
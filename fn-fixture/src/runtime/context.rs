@@ -0,0 +1,75 @@
+//! Tells a fixture which test it is running for.
+
+use std::{
+    collections::BTreeMap,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+/// Where the fixture is running, passed to a fixture that takes
+/// `&Context` as a parameter after its input.
+#[derive(Debug, Clone, Copy)]
+pub struct Context {
+    test_path: &'static str,
+    dir: &'static Path,
+    resources: &'static [(&'static str, &'static [u8])],
+}
+
+impl Context {
+    /// The context of the test at `test_path`, in the module at
+    /// `prefix`, that compares against `expected_file`, with its
+    /// embedded `resources`.
+    #[doc(hidden)]
+    pub fn new(
+        test_path: &'static str,
+        prefix: &str,
+        expected_file: &'static str,
+        resources: &'static [(&'static str, &'static [u8])],
+    ) -> Context {
+        Context {
+            test_path: super::relative(test_path, prefix),
+            dir: Path::new(expected_file)
+                .parent()
+                .unwrap_or_else(|| panic!("No fixture directory of {:?}", expected_file)),
+            resources,
+        }
+    }
+
+    /// The fixture directory, containing the input.
+    pub fn dir(&self) -> &'static Path {
+        self.dir
+    }
+
+    /// The path of the test's folder from the base folder, like
+    /// `examples::bad_number`.
+    pub fn test_path(&self) -> &'static str {
+        self.test_path
+    }
+
+    /// The name of the test's folder, like `bad_number`.
+    pub fn name(&self) -> &'static str {
+        self.test_path
+            .rsplit("::")
+            .next()
+            .unwrap_or(self.test_path)
+    }
+
+    /// The path of the file `name` next to the input.
+    ///
+    /// The file is read when the fixture runs, so changing it does not
+    /// rebuild the tests.
+    pub fn resource(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    /// The contents of the files matching the `resources(...)` of the
+    /// attribute, by their path from the fixture directory, like
+    /// `include/header.h`.
+    ///
+    /// The files are embedded in the test, so changing one rebuilds it.
+    pub fn resources(&self) -> BTreeMap<&'static str, &'static [u8]> {
+        self.resources.iter().copied().collect()
+    }
+}
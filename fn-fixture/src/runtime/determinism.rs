@@ -0,0 +1,55 @@
+//! Calls a fixture more than once, to find results that change
+//! between runs.
+
+use std::collections::BTreeMap;
+
+use super::{
+    compare::{
+        Comparator,
+        Lines,
+    },
+    output_dir,
+};
+
+/// Renders the fixture `runs` times, panicking with the differences
+/// when any run renders differently than the first.
+///
+/// `render` gives the rendered result and anything else that is kept
+/// from the first run, like its panic.
+#[track_caller]
+pub fn repeat<T>(runs: usize, render: impl FnMut() -> (String, T)) -> (String, T) {
+    repeat_checking(runs, render, || None)
+}
+
+/// As [`repeat`], also panicking when any run writes different files
+/// to its [`OutputDir`](super::output_dir::OutputDir) than the first.
+///
+/// The directory starts empty each run, so its files are read as each
+/// run returns. Those of the last run are left to be compared to the
+/// `<name>.out/` tree.
+#[track_caller]
+pub fn repeat_writing<T>(runs: usize, actual_file: &str, render: impl FnMut() -> (String, T)) -> (String, T) {
+    repeat_checking(runs, render, || Some(output_dir::written(actual_file)))
+}
+
+#[track_caller]
+fn repeat_checking<T>(
+    runs: usize,
+    mut render: impl FnMut() -> (String, T),
+    mut written: impl FnMut() -> Option<BTreeMap<String, Vec<u8>>>,
+) -> (String, T) {
+    let (first, kept) = render();
+    let first_written = written();
+    for run in 2..=runs {
+        let (result, _) = render();
+        if let Err(report) = Lines.compare(&first, &result) {
+            panic!("Run {} of {} differs from the first\n{}", run, runs, report)
+        }
+        if let (Some(first_written), Some(written)) = (&first_written, written()) {
+            if let Err(report) = output_dir::differences(first_written, &written) {
+                panic!("Run {} of {} wrote different output files than the first\n{}", run, runs, report)
+            }
+        }
+    }
+    (first, kept)
+}
@@ -0,0 +1,203 @@
+//! Collects the `log` records and `tracing` events of a fixture, each
+//! rendered as a line of `LEVEL target: message key=value`.
+//!
+//! Only the thread calling the fixture is collected from, so fixtures
+//! running at the same time do not see each other's events.
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+use std::fmt::Display;
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+fn line(level: impl Display, target: &str, message: &str, fields: &[(String, String)]) -> String {
+    let mut line = format!("{} {}: {}", level, target, message);
+    for (key, value) in fields {
+        line.push_str(&format!(" {}={}", key, value));
+    }
+    line.push('\n');
+    line
+}
+
+#[cfg(feature = "log")]
+pub use self::log_records::log;
+
+#[cfg(feature = "log")]
+mod log_records {
+    use std::cell::RefCell;
+
+    use log::{
+        kv::{
+            self,
+            Key,
+            Value,
+            VisitSource,
+        },
+        LevelFilter,
+        Log,
+        Metadata,
+        Record,
+    };
+
+    thread_local! {
+        static RECORDS: RefCell<Option<String>> = const { RefCell::new(None) };
+    }
+
+    /// Passes the records of a collecting thread to it, and ignores
+    /// every other record.
+    struct Collector;
+
+    impl Log for Collector {
+        fn enabled(&self, _: &Metadata) -> bool {
+            RECORDS.with(|records| records.borrow().is_some())
+        }
+
+        fn log(&self, record: &Record) {
+            RECORDS.with(|records| {
+                if let Some(records) = records.borrow_mut().as_mut() {
+                    let mut fields = Fields(Vec::new());
+                    let _ = record.key_values().visit(&mut fields);
+                    records.push_str(&super::line(
+                        record.level(),
+                        record.target(),
+                        &record.args().to_string(),
+                        &fields.0,
+                    ));
+                }
+            });
+        }
+
+        fn flush(&self) {}
+    }
+
+    struct Fields(Vec<(String, String)>);
+
+    impl<'kvs> VisitSource<'kvs> for Fields {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+            self.0.push((key.to_string(), format!("{:?}", value)));
+            Ok(())
+        }
+    }
+
+    /// Calls the fixture, collecting the `log` records from its thread.
+    ///
+    /// The collector is installed as the logger the first time, and
+    /// panics if there already is another logger.
+    pub fn log<T>(call: impl FnOnce() -> T) -> (T, String) {
+        static COLLECTOR: Collector = Collector;
+        static INSTALL: std::sync::Once = std::sync::Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&COLLECTOR)
+                .unwrap_or_else(|_| panic!("capture(log) needs to be the logger, but there already is another"));
+            log::set_max_level(LevelFilter::Trace);
+        });
+        let outer = RECORDS.with(|records| records.replace(Some(String::new())));
+        let result = call();
+        let records = RECORDS
+            .with(|records| records.replace(outer))
+            .unwrap_or_default();
+        (result, records)
+    }
+}
+
+#[cfg(feature = "tracing")]
+pub use self::tracing_events::tracing;
+
+#[cfg(feature = "tracing")]
+mod tracing_events {
+    use std::{
+        fmt::Debug,
+        sync::{
+            atomic::{
+                AtomicU64,
+                Ordering,
+            },
+            Arc,
+            Mutex,
+            PoisonError,
+        },
+    };
+
+    use tracing::{
+        field::{
+            Field,
+            Visit,
+        },
+        span,
+        subscriber::with_default,
+        Event,
+        Metadata,
+        Subscriber,
+    };
+
+    /// Records every event, and gives spans ids without recording them.
+    struct Collector {
+        events: Arc<Mutex<String>>,
+        spans: AtomicU64,
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(self.spans.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields {
+                message: String::new(),
+                fields: Vec::new(),
+            };
+            event.record(&mut fields);
+            let metadata = event.metadata();
+            self.events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_str(&super::line(
+                    metadata.level(),
+                    metadata.target(),
+                    &fields.message,
+                    &fields.fields,
+                ));
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    struct Fields {
+        message: String,
+        fields: Vec<(String, String)>,
+    }
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.message = format!("{:?}", value);
+            } else {
+                self.fields.push((field.name().to_string(), format!("{:?}", value)));
+            }
+        }
+    }
+
+    /// Calls the fixture, collecting the `tracing` events from its
+    /// thread with a default subscriber of its own.
+    pub fn tracing<T>(call: impl FnOnce() -> T) -> (T, String) {
+        let events = Arc::new(Mutex::new(String::new()));
+        let collector = Collector {
+            events: events.clone(),
+            spans: AtomicU64::new(0),
+        };
+        let result = with_default(collector, call);
+        let events = events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        (result, events)
+    }
+}
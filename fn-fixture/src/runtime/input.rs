@@ -0,0 +1,75 @@
+//! Copies an `input/` directory for a fixture, so that it may change
+//! the files without changing the test.
+
+use std::{
+    env,
+    fs,
+    io,
+    ops::Deref,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process,
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+/// A fresh copy of an `input/` directory, removed once dropped.
+///
+/// The fixture borrows it as a `&Path` for the length of its call.
+pub struct Copied(PathBuf);
+
+impl Copied {
+    /// Copies the directory at `dir` into a fresh temporary directory.
+    pub fn of(dir: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let copy = env::temp_dir().join(format!(
+            "fn-fixture-{}-input-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed),
+        ));
+        let _ = fs::remove_dir_all(&copy);
+        // Owned before copying, so a partial copy is removed as well
+        let copied = Copied(copy);
+        copy_dir(Path::new(dir), &copied)
+            .unwrap_or_else(|err| panic!("Copying {:?} to {:?}: {:?}", dir, copied.0, err));
+        copied
+    }
+}
+
+impl Deref for Copied {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for Copied {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Copied {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,192 @@
+//! Runs a fixture in a child process of the test binary, so that an
+//! abort, a stack overflow, or an exit is recorded instead of ending
+//! every test.
+
+use std::{
+    env,
+    fs,
+    io::Read,
+    path::PathBuf,
+    process::{
+        self,
+        Command,
+        ExitStatus,
+        Stdio,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use super::Interrupted;
+
+/// Set in the child to the path of the test it runs.
+const CHILD: &str = "FN_FIXTURE_ISOLATED";
+/// Set in the child to the file its rendered result is written to.
+const RESULT: &str = "FN_FIXTURE_ISOLATED_RESULT";
+/// The number of stderr lines kept when the child dies.
+const STDERR_TAIL: usize = 10;
+/// How often the child is checked on while it has a timeout.
+const POLL: Duration = Duration::from_millis(10);
+
+/// Renders the fixture in a child process, running the test at
+/// `test_path` alone.
+///
+/// In the child, `render` is called and its result handed to the
+/// parent, then the child exits. In the parent, the child's result is
+/// returned, or how the child died if it never gave one. The child is
+/// killed once `timeout` passes.
+pub fn run(test_path: &str, timeout: Option<Duration>, render: impl FnOnce() -> String) -> Result<String, Interrupted> {
+    if env::var(CHILD).is_ok_and(|child| child == test_path) {
+        let result_file = env::var_os(RESULT)
+            .unwrap_or_else(|| panic!("Missing {} in isolated {}", RESULT, test_path));
+        let result = render();
+        fs::write(&result_file, result)
+            .unwrap_or_else(|err| panic!("Writing isolated result to {:?}: {:?}", result_file, err));
+        process::exit(0)
+    }
+
+    let result_file = result_file(test_path);
+    let _ = fs::remove_file(&result_file);
+    let filter = test_path
+        .split_once("::")
+        .map_or(test_path, |(_, path)| path);
+    let mut child = Command::new(env::current_exe().expect("Locating the test binary"))
+        .args([filter, "--exact", "--include-ignored", "--nocapture", "--test-threads", "1", "-q"])
+        .env(CHILD, test_path)
+        .env(RESULT, &result_file)
+        .env("RUST_BACKTRACE", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| panic!("Running isolated {}: {:?}", test_path, err));
+    let stderr = child.stderr.take().map(|mut stderr| thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = stderr.read_to_end(&mut buffer);
+        buffer
+    }));
+
+    let status = match timeout {
+        Some(timeout) => {
+            let deadline = Instant::now() + timeout;
+            loop {
+                if let Some(status) = child
+                    .try_wait()
+                    .unwrap_or_else(|err| panic!("Waiting for isolated {}: {:?}", test_path, err))
+                {
+                    break status
+                }
+                if Instant::now() >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(Interrupted::Timeout {
+                        after: format!("{:?}", timeout),
+                    })
+                }
+                thread::sleep(POLL.min(deadline - Instant::now()));
+            }
+        },
+        None => child
+            .wait()
+            .unwrap_or_else(|err| panic!("Waiting for isolated {}: {:?}", test_path, err)),
+    };
+    let stderr = stderr
+        .and_then(|stderr| stderr.join().ok())
+        .unwrap_or_default();
+
+    match fs::read_to_string(&result_file) {
+        Ok(result) => {
+            let _ = fs::remove_file(&result_file);
+            Ok(result)
+        },
+        Err(_) => Err(Interrupted::Died {
+            status: describe(status),
+            stderr: tail(&String::from_utf8_lossy(&stderr)),
+        }),
+    }
+}
+
+fn result_file(test_path: &str) -> PathBuf {
+    let name: String = test_path
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    env::temp_dir().join(format!("fn-fixture-{}-{}", process::id(), name))
+}
+
+/// The exit code or signal, without platform details like a core dump.
+fn describe(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            let name = match signal {
+                4 => " (SIGILL)",
+                6 => " (SIGABRT)",
+                7 => " (SIGBUS)",
+                8 => " (SIGFPE)",
+                9 => " (SIGKILL)",
+                11 => " (SIGSEGV)",
+                15 => " (SIGTERM)",
+                _ => "",
+            };
+            return format!("signal {}{}", signal, name)
+        }
+    }
+    // A crash on Windows exits with the code of its exception
+    #[cfg(windows)]
+    {
+        if let Some(code) = status.code().map(|code| code as u32).filter(|code| code & 0xC000_0000 == 0xC000_0000) {
+            let name = match code {
+                0xC000_0005 => " (STATUS_ACCESS_VIOLATION)",
+                0xC000_001D => " (STATUS_ILLEGAL_INSTRUCTION)",
+                0xC000_00FD => " (STATUS_STACK_OVERFLOW)",
+                0xC000_0409 => " (STATUS_STACK_BUFFER_OVERRUN)",
+                _ => "",
+            };
+            return format!("exception {:#010X}{}", code, name)
+        }
+    }
+    match status.code() {
+        Some(code) => format!("exit code {}", code),
+        None => status.to_string(),
+    }
+}
+
+/// The last lines of the child's stderr, without thread ids.
+fn tail(stderr: &str) -> Vec<String> {
+    let lines: Vec<&str> = stderr
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    lines[lines.len().saturating_sub(STDERR_TAIL)..]
+        .iter()
+        .map(|line| without_thread_id(line))
+        .collect()
+}
+
+/// `thread 'name' (1234) panicked` to `thread 'name' panicked`.
+fn without_thread_id(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("thread '") {
+        let name_start = start + "thread '".len();
+        let name_end = match rest[name_start..].find('\'') {
+            Some(end) => name_start + end + 1,
+            None => break,
+        };
+        result.push_str(&rest[..name_end]);
+        rest = &rest[name_end..];
+        if let Some(id) = rest.strip_prefix(" (") {
+            let digits = id.bytes().take_while(u8::is_ascii_digit).count();
+            if digits > 0 && id[digits..].starts_with(')') {
+                rest = &id[(digits + 1)..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,155 @@
+//! Captures what a fixture writes to stdout and stderr, by pointing
+//! the file descriptors at a temporary file while it runs.
+//!
+//! The test harness keeps `print!` and `eprint!` of a test to itself,
+//! so these only reach the file descriptors in an isolated child,
+//! which is run with `--nocapture`.
+
+use std::{
+    io::Write,
+    sync::{
+        Mutex,
+        PoisonError,
+    },
+};
+
+/// What the fixture wrote to stdout and stderr.
+#[derive(Default)]
+pub struct Captured {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Calls the fixture, capturing stdout and stderr as asked.
+///
+/// The file descriptors belong to the whole process, so only one
+/// fixture at a time is captured.
+pub fn capture<T>(stdout: bool, stderr: bool, call: impl FnOnce() -> T) -> (T, Captured) {
+    static CAPTURING: Mutex<()> = Mutex::new(());
+    let _capturing = CAPTURING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let redirects = (
+        stdout.then(|| redirect::Redirect::start(redirect::STDOUT)),
+        stderr.then(|| redirect::Redirect::start(redirect::STDERR)),
+    );
+    let result = call();
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    let captured = Captured {
+        stdout: redirects.0.map(redirect::Redirect::finish).unwrap_or_default(),
+        stderr: redirects.1.map(redirect::Redirect::finish).unwrap_or_default(),
+    };
+    (result, captured)
+}
+
+#[cfg(unix)]
+mod redirect {
+    use std::{
+        env,
+        fs::{
+            self,
+            File,
+            OpenOptions,
+        },
+        io::{
+            Read,
+            Seek,
+            SeekFrom,
+        },
+        os::unix::io::{
+            AsRawFd,
+            RawFd,
+        },
+        process,
+        sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    };
+
+    pub(super) const STDOUT: RawFd = libc::STDOUT_FILENO;
+    pub(super) const STDERR: RawFd = libc::STDERR_FILENO;
+
+    /// A file descriptor pointed at a temporary file, until finished
+    /// or dropped.
+    pub(super) struct Redirect {
+        fd: RawFd,
+        saved: RawFd,
+        file: File,
+    }
+
+    impl Redirect {
+        pub(super) fn start(fd: RawFd) -> Self {
+            static COUNT: AtomicUsize = AtomicUsize::new(0);
+            let path = env::temp_dir().join(format!(
+                "fn-fixture-output-{}-{}",
+                process::id(),
+                COUNT.fetch_add(1, Ordering::Relaxed),
+            ));
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .unwrap_or_else(|err| panic!("Creating {:?} to capture output: {:?}", path, err));
+            // Still open, so it lives on until it is closed
+            let _ = fs::remove_file(&path);
+            // SAFETY: both descriptors are open, and fd is restored when dropped
+            let saved = unsafe { libc::dup(fd) };
+            if saved < 0 || unsafe { libc::dup2(file.as_raw_fd(), fd) } < 0 {
+                panic!("Redirecting file descriptor {}: {:?}", fd, std::io::Error::last_os_error())
+            }
+            Redirect {
+                fd,
+                saved,
+                file,
+            }
+        }
+
+        pub(super) fn finish(mut self) -> String {
+            self.restore();
+            let mut bytes = Vec::new();
+            self.file
+                .seek(SeekFrom::Start(0))
+                .and_then(|_| self.file.read_to_end(&mut bytes))
+                .unwrap_or_else(|err| panic!("Reading captured output: {:?}", err));
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+
+        fn restore(&mut self) {
+            if self.saved >= 0 {
+                // SAFETY: saved was duplicated from fd in start
+                unsafe {
+                    libc::dup2(self.saved, self.fd);
+                    libc::close(self.saved);
+                }
+                self.saved = -1;
+            }
+        }
+    }
+
+    impl Drop for Redirect {
+        fn drop(&mut self) {
+            self.restore();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod redirect {
+    pub(super) const STDOUT: i32 = 1;
+    pub(super) const STDERR: i32 = 2;
+
+    pub(super) struct Redirect;
+
+    impl Redirect {
+        pub(super) fn start(_: i32) -> Self {
+            unreachable!("Capturing stdout and stderr is rejected when the tests build off Unix")
+        }
+
+        pub(super) fn finish(self) -> String {
+            String::new()
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! A scratch directory for a fixture to write files into, compared
+//! against a `<name>.out/` tree after the fixture returns.
+
+use std::{
+    collections::{
+        hash_map::DefaultHasher,
+        BTreeMap,
+        BTreeSet,
+    },
+    env,
+    fs,
+    hash::{
+        Hash,
+        Hasher,
+    },
+    io,
+    ops::Deref,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use super::compare::{
+    Comparator,
+    Lines,
+};
+
+/// The directory a fixture writes its output files into, passed to a
+/// fixture that takes `&OutputDir` as a parameter after its input.
+///
+/// It starts empty each run. After the fixture returns, every file in
+/// it is compared to the same file in the `<name>.out/` tree beside
+/// the expected file.
+#[derive(Debug)]
+pub struct OutputDir {
+    path: PathBuf,
+}
+
+impl OutputDir {
+    /// An empty scratch directory for the test writing `actual_file`.
+    #[doc(hidden)]
+    pub fn new(actual_file: &str) -> OutputDir {
+        let path = scratch(actual_file);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path)
+            .unwrap_or_else(|err| panic!("Creating output directory {:?}: {:?}", path, err));
+        OutputDir {
+            path,
+        }
+    }
+
+    /// The path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for OutputDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for OutputDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// The scratch directory of the test, which is the same in an isolated
+/// child as in its parent.
+fn scratch(actual_file: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    actual_file.hash(&mut hasher);
+    env::temp_dir().join(format!("fn-fixture-out-{:016x}", hasher.finish()))
+}
+
+/// Compares the files the fixture wrote to the `<name>.out/` tree,
+/// writing them to `<name>.actual.out/` when they differ or there is
+/// nothing to compare to.
+///
+/// The comparison is reported by [`check`], after the result itself
+/// is checked.
+pub fn compare(expected_file: &str, actual_file: &str) -> Result<(), String> {
+    let scratch = scratch(actual_file);
+    let expected_dir = Path::new(expected_file).with_extension("out");
+    let actual_dir = Path::new(actual_file).with_extension("out");
+    let actual = written(actual_file);
+    let _ = fs::remove_dir_all(&actual_dir);
+
+    // An empty tree cannot be committed, so it is the same as none
+    let report = if expected_dir.is_dir() {
+        let expected = read_tree(&expected_dir)
+            .unwrap_or_else(|err| panic!("Reading expected output from {:?}: {:?}", expected_dir, err));
+        differences(&expected, &actual)
+            .map_err(|report| format!("Output differs from {}\n{}", expected_dir.display(), report))
+    } else if actual.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("No expected output set: {}", actual_dir.display()))
+    };
+    if report.is_err() {
+        write_tree(&actual_dir, &actual)
+            .unwrap_or_else(|err| panic!("Writing actual output to {:?}: {:?}", actual_dir, err));
+    }
+    let _ = fs::remove_dir_all(&scratch);
+    report
+}
+
+/// The files the fixture wrote so far, by their path in the directory.
+pub(super) fn written(actual_file: &str) -> BTreeMap<String, Vec<u8>> {
+    let scratch = scratch(actual_file);
+    read_tree(&scratch)
+        .unwrap_or_else(|err| panic!("Reading output directory {:?}: {:?}", scratch, err))
+}
+
+/// Fails with the report of [`compare`], if any.
+#[track_caller]
+pub fn check(compared: Result<(), String>) {
+    if let Err(report) = compared {
+        panic!("{}", report)
+    }
+}
+
+/// The added, removed, and changed files, with the lines that changed.
+pub(super) fn differences(expected: &BTreeMap<String, Vec<u8>>, actual: &BTreeMap<String, Vec<u8>>) -> Result<(), String> {
+    let names: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+    let mut report = Vec::new();
+    for name in names {
+        match (expected.get(name), actual.get(name)) {
+            (Some(_), None) => report.push(format!("removed: {}", name)),
+            (None, Some(_)) => report.push(format!("added: {}", name)),
+            (Some(expected), Some(actual)) if expected != actual => {
+                report.push(format!("changed: {}", name));
+                match (std::str::from_utf8(expected), std::str::from_utf8(actual)) {
+                    (Ok(expected), Ok(actual)) => if let Err(lines) = Lines.compare(expected, actual) {
+                        report.push(lines);
+                    },
+                    _ => report.push(format!(
+                        "Binary contents differ ({} bytes != {} bytes)",
+                        expected.len(),
+                        actual.len(),
+                    )),
+                }
+            },
+            _ => {},
+        }
+    }
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report.join("\n"))
+    }
+}
+
+/// Every file under `dir`, by its path from `dir` with `/` separators.
+fn read_tree(dir: &Path) -> io::Result<BTreeMap<String, Vec<u8>>> {
+    fn read(root: &Path, dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                read(root, &path, files)?;
+            } else {
+                let name = path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                files.insert(name, fs::read(&path)?);
+            }
+        }
+        Ok(())
+    }
+    let mut files = BTreeMap::new();
+    if dir.is_dir() {
+        read(dir, dir, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn write_tree(dir: &Path, files: &BTreeMap<String, Vec<u8>>) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (name, contents) in files {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+//! Normalizes the rendered result before it is compared or written,
+//! so that snapshots do not depend on the machine that made them.
+
+use std::path::{
+    Path,
+    MAIN_SEPARATOR,
+};
+
+/// Rewrites part of a rendered result.
+pub trait Normalizer {
+    fn normalize(&self, text: &str) -> String;
+}
+
+impl<F: Fn(&str) -> String> Normalizer for F {
+    fn normalize(&self, text: &str) -> String {
+        self(text)
+    }
+}
+
+/// Applies each normalizer in order.
+pub fn apply(text: String, normalizers: &[&dyn Normalizer]) -> String {
+    normalizers
+        .iter()
+        .fold(text, |text, normalizer| normalizer.normalize(&text))
+}
+
+/// Replaces the crate's directory with `[manifest_dir]`.
+///
+/// The directory is also found in its canonical form, and when
+/// escaped by up to three layers of `{:?}` (like a path in a
+/// compile error in a rendered string).
+pub struct ManifestDir(pub &'static str);
+
+impl Normalizer for ManifestDir {
+    fn normalize(&self, text: &str) -> String {
+        let canonical = Path::new(self.0)
+            .canonicalize()
+            .ok()
+            .and_then(|path| path.to_str().map(str::to_string));
+        let mut forms = Vec::new();
+        for form in std::iter::once(self.0.to_string()).chain(canonical) {
+            let mut form = form;
+            for _ in 0..4 {
+                let escaped = escape(&form);
+                forms.push(form);
+                form = escaped;
+            }
+        }
+        // Most escaped first, so that the plain form cannot match a part of it
+        forms.sort_by_key(|form| std::cmp::Reverse(form.len()));
+        forms.dedup();
+        forms
+            .iter()
+            .fold(text.to_string(), |text, form| text.replace(form.as_str(), "[manifest_dir]"))
+    }
+}
+
+fn escape(text: &str) -> String {
+    let debug = format!("{:?}", text);
+    debug[1..(debug.len() - 1)].to_string()
+}
+
+/// Replaces `\` path separators with `/` on platforms that use `\`.
+///
+/// Separators are recognized between path characters as a single
+/// backslash that does not start an escape, or as a backslash
+/// escaped by up to three layers of `{:?}`.
+pub struct PathSeparators;
+
+impl Normalizer for PathSeparators {
+    fn normalize(&self, text: &str) -> String {
+        if MAIN_SEPARATOR != '\\' {
+            return text.to_string()
+        }
+        let path_char = |c: Option<char>| c.is_some_and(|c|
+            c.is_alphanumeric() || "_-.:?$~[]".contains(c)
+        );
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.char_indices().peekable();
+        let mut previous = None;
+        while let Some((start, c)) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                previous = Some(c);
+                continue;
+            }
+            let mut end = start + 1;
+            while let Some(&(index, '\\')) = chars.peek() {
+                chars.next();
+                end = index + 1;
+            }
+            let next = text[end..].chars().next();
+            let separator = path_char(previous) && path_char(next) && match end - start {
+                1 => !matches!(next, Some('n' | 't' | 'r' | '0' | 'u' | 'x')),
+                2 | 4 | 8 => true,
+                _ => false,
+            };
+            if separator {
+                result.push('/');
+            } else {
+                result.push_str(&text[start..end]);
+            }
+            previous = Some('\\');
+        }
+        result
+    }
+}
+
+/// Replaces hexadecimal addresses, like `0x7ffd5fbff8a8`, with `[address]`.
+pub struct Addresses;
+
+impl Normalizer for Addresses {
+    fn normalize(&self, text: &str) -> String {
+        replace_matches(text, "[address]", |bytes| {
+            if !bytes.starts_with(b"0x") {
+                return None
+            }
+            let digits = hex_run(&bytes[2..]);
+            if digits >= 4 {
+                Some(2 + digits)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Replaces UUIDs, like `67e55044-10b1-426f-9247-bb680e5fe0c8`, with `[uuid]`.
+pub struct Uuids;
+
+impl Normalizer for Uuids {
+    fn normalize(&self, text: &str) -> String {
+        replace_matches(text, "[uuid]", |bytes| {
+            let mut length = 0;
+            for (index, &group) in [8, 4, 4, 4, 12].iter().enumerate() {
+                if index > 0 {
+                    if bytes.get(length) != Some(&b'-') {
+                        return None
+                    }
+                    length += 1;
+                }
+                if hex_run(&bytes[length..]) != group {
+                    return None
+                }
+                length += group;
+            }
+            Some(length)
+        })
+    }
+}
+
+/// Replaces RFC 3339 timestamps, like `2021-02-03T04:05:06.789Z`,
+/// with `[timestamp]`.
+///
+/// The date and time may also be separated by a space, and the
+/// fractional seconds and offset are optional.
+pub struct Timestamps;
+
+impl Normalizer for Timestamps {
+    fn normalize(&self, text: &str) -> String {
+        replace_matches(text, "[timestamp]", |bytes| {
+            let digits = |at: usize, count: usize| (at..(at + count))
+                .all(|at| bytes.get(at).is_some_and(u8::is_ascii_digit));
+            let byte = |at: usize, expected: &[u8]| bytes
+                .get(at)
+                .is_some_and(|byte| expected.contains(byte));
+            let valid = digits(0, 4) && byte(4, b"-") && digits(5, 2) && byte(7, b"-") && digits(8, 2)
+                && byte(10, b"T ") && digits(11, 2) && byte(13, b":") && digits(14, 2)
+                && byte(16, b":") && digits(17, 2);
+            if !valid {
+                return None
+            }
+            let mut length = 19;
+            if byte(length, b".") && digits(length + 1, 1) {
+                length += 1;
+                while digits(length, 1) {
+                    length += 1;
+                }
+            }
+            if byte(length, b"Z") {
+                length += 1;
+            } else if byte(length, b"+-") && digits(length + 1, 2) && byte(length + 3, b":") && digits(length + 4, 2) {
+                length += 6;
+            }
+            Some(length)
+        })
+    }
+}
+
+/// Replaces every match of a regular expression.
+///
+/// The replacement may refer to capture groups, like `$1`.
+#[cfg(feature = "regex")]
+pub struct Regex {
+    regex: regex::Regex,
+    replacement: &'static str,
+}
+
+#[cfg(feature = "regex")]
+impl Regex {
+    pub fn new(pattern: &str, replacement: &'static str) -> Self {
+        Regex {
+            regex: regex::Regex::new(pattern)
+                .unwrap_or_else(|err| panic!("Invalid redaction regex {:?}: {}", pattern, err)),
+            replacement,
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Normalizer for Regex {
+    fn normalize(&self, text: &str) -> String {
+        self.regex.replace_all(text, self.replacement).into_owned()
+    }
+}
+
+fn hex_run(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .take_while(|byte| byte.is_ascii_hexdigit())
+        .count()
+}
+
+/// Replaces every match that starts and ends on a word boundary.
+///
+/// `matcher` is given the rest of the text, and returns the length
+/// of the match at its start.
+fn replace_matches(text: &str, placeholder: &str, matcher: impl Fn(&[u8]) -> Option<usize>) -> String {
+    let bytes = text.as_bytes();
+    let word = |at: usize| bytes
+        .get(at)
+        .is_some_and(|&byte| byte.is_ascii_alphanumeric() || byte == b'_');
+    let mut result = String::with_capacity(text.len());
+    let mut start = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let found = if index == 0 || !word(index - 1) {
+            matcher(&bytes[index..])
+                .filter(|&length| !word(index + length))
+        } else {
+            None
+        };
+        match found {
+            Some(length) => {
+                result.push_str(&text[start..index]);
+                result.push_str(placeholder);
+                index += length;
+                start = index;
+            },
+            None => index += 1,
+        }
+    }
+    result.push_str(&text[start..]);
+    result
+}
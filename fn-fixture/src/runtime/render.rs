@@ -0,0 +1,263 @@
+//! Turns a fixture's outcome into the text stored in its expected file.
+
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::Debug,
+};
+
+use super::{
+    Interrupted,
+    Outcome,
+};
+
+impl Interrupted {
+    /// The rendering in the fixture's output format, chosen by the
+    /// expected file's extension.
+    pub fn render(&self, extension: &str) -> String {
+        match extension {
+            #[cfg(feature = "serde")]
+            "json" => structured::to_json(self),
+            #[cfg(feature = "serde")]
+            "yaml" => structured::to_yaml(self),
+            #[cfg(feature = "serde")]
+            "ron" => structured::to_ron(self),
+            _ => format!("{:#?}\n", self),
+        }
+    }
+}
+
+/// The default rendering, `{:#?}` of the whole outcome.
+pub fn debug<R: Debug>(outcome: Outcome<R>) -> String {
+    format!("{:#?}\n", outcome)
+}
+
+/// `{:#?}` of the outcome, except that an `Err` is rendered as its
+/// message and the messages of its [`Error::source`] chain.
+///
+/// Anything convertible into a `Box<dyn Error>` works as the error,
+/// including `Box<dyn Error>` itself and `String`.
+pub fn error_chain<'a, T: Debug, E: Into<Box<dyn Error + 'a>>>(outcome: Outcome<Result<T, E>>) -> String {
+    debug(outcome.map(|result| result.map_err(|error| ErrorChain::new(error.into()))))
+}
+
+/// The rendering of an error by [`error_chain`].
+struct ErrorChain {
+    message: String,
+    sources: Vec<String>,
+}
+
+impl Debug for ErrorChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorChain")
+            .field("message", &self.message)
+            .field("sources", &self.sources)
+            .finish()
+    }
+}
+
+impl ErrorChain {
+    fn new(error: Box<dyn Error + '_>) -> Self {
+        let mut sources = Vec::new();
+        let mut source = error.source();
+        while let Some(error) = source {
+            sources.push(error.to_string());
+            source = error.source();
+        }
+        ErrorChain {
+            message: error.to_string(),
+            sources,
+        }
+    }
+}
+
+/// Sorts the entries of every map and set in a `{:#?}` rendering,
+/// so that hash-based collections render the same every run.
+///
+/// A map or set is a `{` that ends a line and is not preceded by a
+/// struct name. Entries are ordered by their rendered text, which
+/// starts with the key, comparing runs of digits by their value.
+pub fn sort_collections(text: String) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut result = sort_lines(&lines).join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+fn sort_lines(lines: &[&str]) -> Vec<String> {
+    let mut result = Vec::with_capacity(lines.len());
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+        if !opens_collection(line) {
+            result.push(line.to_string());
+            continue;
+        }
+        let indent = indentation(line);
+        let close = match lines[index..]
+            .iter()
+            .position(|line| indentation(line) == indent && line[indent..].starts_with('}'))
+        {
+            Some(close) => index + close,
+            // Not rendered by {:#?}, so leave it be
+            None => {
+                result.push(line.to_string());
+                continue;
+            },
+        };
+
+        let mut entries: Vec<Vec<String>> = Vec::new();
+        let mut start = index;
+        for end in (index + 1)..=close {
+            let next = lines[end];
+            let starts_entry = end == close || (
+                indentation(next) == indent + 4
+                    && !next[(indent + 4)..].starts_with(['}', ']', ')'])
+            );
+            if starts_entry {
+                entries.push(sort_lines(&lines[start..end]));
+                start = end;
+            }
+        }
+        entries.sort_by(|left, right| natural_order(&left.join("\n"), &right.join("\n")));
+
+        result.push(line.to_string());
+        result.extend(entries.into_iter().flatten());
+        result.push(lines[close].to_string());
+        index = close + 1;
+    }
+    result
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn opens_collection(line: &str) -> bool {
+    let before = match line.trim_start().strip_suffix('{') {
+        Some(before) => before,
+        None => return false,
+    };
+    before.is_empty() || before.ends_with(": ") || before.ends_with('(')
+}
+
+/// Compares text, except that runs of digits are compared by value.
+fn natural_order(left: &str, right: &str) -> Ordering {
+    let mut left = left.chars().peekable();
+    let mut right = right.chars().peekable();
+    loop {
+        match (left.peek().copied(), right.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let take_digits = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                        digits.push(c);
+                        chars.next();
+                    }
+                    digits
+                };
+                let l = take_digits(&mut left);
+                let r = take_digits(&mut right);
+                let l = l.trim_start_matches('0');
+                let r = r.trim_start_matches('0');
+                match l.len().cmp(&r.len()).then_with(|| l.cmp(r)) {
+                    Ordering::Equal => {},
+                    ordering => return ordering,
+                }
+            },
+            (Some(l), Some(r)) => {
+                match l.cmp(&r) {
+                    Ordering::Equal => {},
+                    ordering => return ordering,
+                }
+                left.next();
+                right.next();
+            },
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use self::structured::*;
+
+#[cfg(feature = "serde")]
+mod structured {
+    use serde::Serialize;
+
+    use super::Outcome;
+
+    /// Serializable form of [`Outcome`], tagged by whether the fixture panicked.
+    ///
+    /// A panic with a registered or primitive payload has its `{:?}`
+    /// as the message, and any other payload has no message.
+    #[derive(Serialize)]
+    enum Envelope<'a, R> {
+        Ok(&'a R),
+        Panic(Option<String>),
+    }
+
+    impl<'a, R> From<&'a Outcome<R>> for Envelope<'a, R> {
+        fn from(outcome: &'a Outcome<R>) -> Self {
+            match outcome {
+                Ok(value) => Envelope::Ok(value),
+                Err(Ok((_, message))) => Envelope::Panic(Some(message.to_string())),
+                Err(Err((_, payload))) => Envelope::Panic(payload
+                    .describe()
+                    .map(|(_, value)| format!("{:?}", value))
+                ),
+            }
+        }
+    }
+
+    fn serialized<S, E: std::fmt::Debug>(format: &str, result: Result<S, E>) -> S {
+        result.unwrap_or_else(|err| panic!("Serializing result to {}: {:?}", format, err))
+    }
+
+    /// Pretty JSON of the outcome, as `{"Ok": ...}` or `{"Panic": ...}`.
+    pub fn json<R: Serialize>(outcome: Outcome<R>) -> String {
+        to_json(&Envelope::from(&outcome))
+    }
+
+    /// YAML of the outcome, as `Ok: ...` or `Panic: ...`.
+    ///
+    /// Enums are written as single-entry maps instead of YAML tags,
+    /// so that other tools need not understand the tags.
+    pub fn yaml<R: Serialize>(outcome: Outcome<R>) -> String {
+        to_yaml(&Envelope::from(&outcome))
+    }
+
+    /// Pretty RON of the outcome, as `Ok(...)` or `Panic(...)`.
+    pub fn ron<R: Serialize>(outcome: Outcome<R>) -> String {
+        to_ron(&Envelope::from(&outcome))
+    }
+
+    pub(super) fn to_json<T: Serialize>(value: &T) -> String {
+        let mut result = serialized("json", serde_json::to_string_pretty(value));
+        result.push('\n');
+        result
+    }
+
+    pub(super) fn to_yaml<T: Serialize>(value: &T) -> String {
+        let mut buffer = Vec::new();
+        serialized(
+            "yaml",
+            serde_yaml::with::singleton_map_recursive::serialize(
+                value,
+                &mut serde_yaml::Serializer::new(&mut buffer),
+            ),
+        );
+        serialized("yaml", String::from_utf8(buffer))
+    }
+
+    pub(super) fn to_ron<T: Serialize>(value: &T) -> String {
+        let mut result = serialized("ron", ron::ser::to_string_pretty(value, Default::default()));
+        result.push('\n');
+        result
+    }
+}
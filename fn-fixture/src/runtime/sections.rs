@@ -0,0 +1,161 @@
+//! Named sections appended after the rendered result, each compared
+//! on its own.
+//!
+//! A section starts with a `--- name ---` line, and continues until
+//! the next section or the end of the file.
+
+use std::{
+    fmt::Debug,
+    path::Path,
+};
+
+use super::PanicDetails;
+
+/// Appends a section to the rendered result.
+pub fn append(mut text: String, name: &str, body: &str) -> String {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&header(name));
+    text.push('\n');
+    text.push_str(body);
+    if !body.is_empty() && !body.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+/// Separates the rendered result from the sections after it.
+pub(super) fn split(text: &str) -> (&str, Vec<(&str, &str)>) {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if let Some(name) = name(line.trim_end_matches(['\r', '\n'])) {
+            starts.push((offset, offset + line.len(), name));
+        }
+        offset += line.len();
+    }
+    let main = &text[..starts.first().map_or(text.len(), |&(start, ..)| start)];
+    let sections = starts
+        .iter()
+        .enumerate()
+        .map(|(index, &(_, body, name))| {
+            let end = starts.get(index + 1).map_or(text.len(), |&(start, ..)| start);
+            (name, &text[body..end])
+        })
+        .collect();
+    (main, sections)
+}
+
+fn header(name: &str) -> String {
+    format!("--- {} ---", name)
+}
+
+fn name(line: &str) -> Option<&str> {
+    line
+        .strip_prefix("--- ")?
+        .strip_suffix(" ---")
+        .filter(|name| !name.is_empty() && !name.contains(' '))
+}
+
+/// Named snapshots taken by the fixture while it runs, each appended
+/// as a section after the result.
+///
+/// A fixture takes `&mut Recorder` as a parameter after its input, and
+/// records intermediate values with [`Recorder::record`].
+#[derive(Debug, Default)]
+pub struct Recorder {
+    sections: Vec<(String, String)>,
+}
+
+impl Recorder {
+    /// Records the `{:#?}` of `value` in the section `name`.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is empty, contains whitespace, or was already recorded.
+    pub fn record<T: Debug + ?Sized>(&mut self, name: &str, value: &T) {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            panic!("Section name {:?} must be non-empty without whitespace", name)
+        }
+        if self.sections.iter().any(|(recorded, _)| recorded == name) {
+            panic!("Section {:?} recorded twice", name)
+        }
+        self.sections.push((name.to_string(), format!("{:#?}", value)));
+    }
+}
+
+/// Appends the sections of `recorder`, in the order they were recorded.
+pub fn recorded(text: String, recorder: Recorder) -> String {
+    recorder
+        .sections
+        .iter()
+        .fold(text, |text, (name, body)| append(text, name, body))
+}
+
+/// Appends the `location` section when the fixture panicked, as the
+/// file, line, and column relative to `manifest_dir`.
+pub fn location(text: String, panic: &Option<PanicDetails>, manifest_dir: &str) -> String {
+    match panic.as_ref().and_then(|panic| panic.location.as_ref()) {
+        Some((file, line, column)) => append(
+            text,
+            "location",
+            &format!("{}:{}:{}", relative(manifest_dir, file), line, column),
+        ),
+        None => text,
+    }
+}
+
+/// Appends the `backtrace` section when the fixture panicked.
+///
+/// Only frames from the crate that `module_path` belongs to are kept,
+/// by name, so the section does not depend on the standard library or
+/// the test harness.
+pub fn backtrace(text: String, panic: &Option<PanicDetails>, module_path: &str) -> String {
+    let backtrace = match panic.as_ref().and_then(|panic| panic.backtrace.as_ref()) {
+        Some(backtrace) => backtrace,
+        None => return text,
+    };
+    let crate_name = module_path
+        .split("::")
+        .next()
+        .unwrap_or(module_path);
+    let prefix = format!("{}::", crate_name);
+    let impl_prefix = format!("<{}::", crate_name);
+    let frames: String = backtrace
+        .lines()
+        .filter_map(|line| {
+            let (index, symbol) = line.trim_start().split_once(": ")?;
+            if index.is_empty() || !index.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None
+            }
+            Some(symbol)
+        })
+        .filter(|symbol| symbol.starts_with(&prefix) || symbol.starts_with(&impl_prefix))
+        .map(|symbol| format!("{}\n", symbol))
+        .collect();
+    append(text, "backtrace", &frames)
+}
+
+/// The path of `file` relative to `manifest_dir`, with `/` separators.
+///
+/// `file!()` is relative to the workspace when the crate is part of
+/// one, so a relative path is looked for in each ancestor.
+fn relative(manifest_dir: &str, file: &str) -> String {
+    let manifest_dir = Path::new(manifest_dir);
+    let path = Path::new(file);
+    let absolute = if path.is_absolute() {
+        Some(path.to_path_buf())
+    } else {
+        manifest_dir
+            .ancestors()
+            .map(|ancestor| ancestor.join(path))
+            .find(|candidate| candidate.is_file())
+    };
+    absolute
+        .as_ref()
+        .and_then(|absolute| absolute.strip_prefix(manifest_dir).ok())
+        .and_then(Path::to_str)
+        .unwrap_or(file)
+        .replace('\\', "/")
+}
@@ -0,0 +1,56 @@
+//! Stops waiting on a fixture that runs past its timeout.
+
+use std::{
+    panic::resume_unwind,
+    sync::mpsc::{
+        channel,
+        RecvTimeoutError,
+    },
+    thread,
+    time::Duration,
+};
+
+use super::Interrupted;
+
+/// The timeout of the test at `test_path`: that of the deepest folder
+/// in `overrides` containing the test, otherwise `default`.
+///
+/// The folders are relative to the module at `prefix`, which is the
+/// module of the fixture's tests.
+pub fn select(test_path: &str, prefix: &str, default: Option<Duration>, overrides: &[(&str, Duration)]) -> Option<Duration> {
+    super::select(test_path, prefix, default, overrides)
+}
+
+/// Calls `render` on a thread of its own, giving up on it once
+/// `timeout` passes.
+///
+/// A thread that timed out cannot be stopped, so it is left running
+/// until the test binary exits. The thread has the name of the current
+/// one, so that its panics read the same.
+pub fn run<T: Send + 'static>(timeout: Option<Duration>, render: impl FnOnce() -> T + Send + 'static) -> Result<T, Interrupted> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Ok(render()),
+    };
+    let (sender, receiver) = channel();
+    let mut builder = thread::Builder::new();
+    if let Some(name) = thread::current().name() {
+        builder = builder.name(name.to_string());
+    }
+    let watched = builder
+        .spawn(move || {
+            let _ = sender.send(render());
+        })
+        .unwrap_or_else(|err| panic!("Spawning fixture thread: {:?}", err));
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => Ok(result),
+        Err(RecvTimeoutError::Timeout) => Err(Interrupted::Timeout {
+            after: format!("{:?}", timeout),
+        }),
+        // Rendering panicked outside of the fixture, so pass it on
+        Err(RecvTimeoutError::Disconnected) => match watched.join() {
+            Err(panic) => resume_unwind(panic),
+            Ok(()) => unreachable!("Fixture thread ended without a result"),
+        },
+    }
+}
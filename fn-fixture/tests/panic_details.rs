@@ -0,0 +1,12 @@
+// Kept apart from self_snapshots.rs, as the snapshots record the
+// line numbers of these panics.
+
+#[fn_fixture::snapshot("snapshot-tests/panic_details", panic_details(location, backtrace))]
+fn parse_port(value: &str) -> u16 {
+    checked_port(value.trim().parse().expect("Not a number"))
+}
+
+fn checked_port(port: u16) -> u16 {
+    assert_ne!(port, 0, "Port 0 is reserved");
+    port
+}
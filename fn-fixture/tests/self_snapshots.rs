@@ -1,3 +1,11 @@
+// The panicking inputs in snapshot-tests/code are intentional.
+#![allow(
+    unreachable_code,
+    non_fmt_panics,
+    clippy::diverging_sub_expression,
+    clippy::type_complexity,
+)]
+
 use ::rustfmt::{
     config::{
         Config,
@@ -8,14 +16,389 @@ use ::rustfmt::{
     Input,
     Summary,
 };
-use std::path::{
-    Path,
-    PathBuf,
+use fn_fixture::runtime::{
+    compare::{
+        Comparator,
+        Exact,
+        Json,
+        Tolerant,
+        Yaml,
+    },
+    redact::{
+        ManifestDir,
+        Normalizer,
+        PathSeparators,
+    },
 };
 
-#[fn_fixture::snapshot("snapshot-tests/code")]
+// The message of an OS error is the platform's own
+#[fn_fixture::snapshot("snapshot-tests/code", variants(unix))]
 fn expected<T: std::fmt::Debug>(t: T) -> T { t }
 
+#[fn_fixture::snapshot("snapshot-tests/sorted", render(sorted))]
+fn sorted<T: std::fmt::Debug>(t: T) -> T { t }
+
+#[fn_fixture::snapshot("snapshot-tests/errors", render(error_chain))]
+fn parse_settings(value: &str) -> Result<Vec<u32>, Box<dyn std::error::Error>> {
+    value
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            assert!(!line.is_empty(), "Blank line {}", index + 1);
+            line
+                .parse()
+                .map_err(|source| SettingError { line: index + 1, source }.into())
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct SettingError {
+    line: usize,
+    source: std::num::ParseIntError,
+}
+
+impl std::fmt::Display for SettingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid setting on line {}", self.line)
+    }
+}
+
+impl std::error::Error for SettingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[fn_fixture::snapshot("snapshot-tests/structured", output = "json")]
+fn to_json<T: serde::Serialize>(t: T) -> T { t }
+
+#[fn_fixture::snapshot("snapshot-tests/structured", output = "yaml")]
+fn to_yaml<T: serde::Serialize>(t: T) -> T { t }
+
+#[fn_fixture::snapshot("snapshot-tests/structured", output = "ron")]
+fn to_ron<T: serde::Serialize>(t: T) -> T { t }
+
+#[fn_fixture::snapshot("snapshot-tests/compare/tolerant")]
+fn compare_tolerant(documents: (&str, &str)) -> Result<(), Vec<String>> {
+    let (expected, actual) = documents;
+    Tolerant { float_tolerance: 1e-9 }
+        .compare(expected, actual)
+        .map_err(|report| report.lines().map(str::to_string).collect())
+}
+
+// The default check, with the expected text in a file of its own
+#[fn_fixture::snapshot("snapshot-tests/compare/exact")]
+fn compare_exact(documents: (&str, &str), context: &fn_fixture::Context) -> Result<(), Vec<String>> {
+    let (expected, actual) = documents;
+    let name = context.test_path().replace("::", "_");
+    let expected_file = std::env::temp_dir().join(format!("fn-fixture-{}-{}", std::process::id(), name));
+    std::fs::write(&expected_file, expected).unwrap();
+    let expected_file = expected_file.to_str().unwrap();
+    let checked = std::panic::catch_unwind(|| fn_fixture::runtime::check(actual, &None, expected_file, "", &Exact));
+    let _ = std::fs::remove_file(expected_file);
+    checked.map_err(|panic| panic
+        .downcast_ref::<String>()
+        .unwrap()
+        .replace(expected_file, "[expected]")
+        .lines()
+        .map(str::to_string)
+        .collect()
+    )
+}
+
+#[fn_fixture::snapshot("snapshot-tests/compare/json")]
+fn compare_json(documents: (&str, &str)) -> Result<(), Vec<String>> {
+    let (expected, actual) = documents;
+    Json { float_tolerance: 1e-9 }
+        .compare(expected, actual)
+        .map_err(|report| report.lines().map(str::to_string).collect())
+}
+
+#[fn_fixture::snapshot("snapshot-tests/compare/yaml")]
+fn compare_yaml(documents: (&str, &str)) -> Result<(), Vec<String>> {
+    let (expected, actual) = documents;
+    Yaml::default()
+        .compare(expected, actual)
+        .map_err(|report| report.lines().map(str::to_string).collect())
+}
+
+#[fn_fixture::snapshot(
+    "snapshot-tests/redact",
+    redact(addresses, uuids, timestamps),
+    redact(regex(r"\b\d+ms\b", "[duration]"), with(hide_passwords)),
+)]
+fn redacted(text: &str) -> Vec<&str> {
+    text.lines().collect()
+}
+
+fn hide_passwords(text: &str) -> String {
+    text.replace("hunter2", "*******")
+}
+
+#[fn_fixture::snapshot("snapshot-tests/payloads", panic_payloads(std::io::Error, PayloadError))]
+fn raise(payload: Box<dyn std::any::Any + Send>) {
+    std::panic::resume_unwind(payload)
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct PayloadError {
+    code: u32,
+}
+
+// A crash on Windows is an exception rather than a signal
+#[fn_fixture::snapshot("snapshot-tests/isolated", isolate, timeout = "2s", variants(windows))]
+fn terminate(how: &str) -> u32 {
+    match how {
+        "abort" => std::process::abort(),
+        "hang" => loop {
+            std::thread::sleep(std::time::Duration::from_secs(1))
+        },
+        "exit" => std::process::exit(3),
+        "overflow" => overflow(0),
+        "panic" => panic!("Unwound as usual"),
+        value => value.parse().unwrap(),
+    }
+}
+
+fn overflow(depth: u64) -> u32 {
+    let frame = std::hint::black_box([depth; 64]);
+    if frame[0] == u64::MAX {
+        0
+    } else {
+        overflow(depth + 1) + 1
+    }
+}
+
+#[fn_fixture::snapshot("snapshot-tests/timeouts", timeout = "500ms", timeout(patient = "10s"))]
+fn sleep_for(millis: &str) -> u64 {
+    let millis = millis.parse().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(millis));
+    millis
+}
+
+// A generic fixture with a timeout keeps its generics as written
+#[fn_fixture::snapshot("snapshot-tests/timeouts", timeout = "10s")]
+fn waited_for<T: std::fmt::Debug>(millis: T) -> T { millis }
+
+#[fn_fixture::snapshot("snapshot-tests/determinism", determinism = 3, render(sorted))]
+fn count_words(text: &str) -> std::collections::HashMap<&str, usize> {
+    let mut counts = std::collections::HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(unix)]
+#[fn_fixture::snapshot("snapshot-tests/output", capture(stdout, stderr))]
+fn greet(name: &str) -> usize {
+    println!("Hello, {}!", name);
+    eprintln!("Greeting {} bytes", name.len());
+    assert!(!name.is_empty(), "Nobody to greet");
+    println!("Goodbye, {}!", name);
+    name.len()
+}
+
+#[fn_fixture::snapshot("snapshot-tests/events", capture(log, tracing))]
+fn parse_level(value: &str) -> Option<u8> {
+    log::debug!("Parsing {:?}", value);
+    match value.parse() {
+        Ok(level) if level > 5 => {
+            log::warn!(target: "levels", max = 5; "Level too high");
+            tracing::warn!(level, "Clamping level");
+            Some(5)
+        },
+        Ok(level) => Some(level),
+        Err(err) => {
+            tracing::error!(error = %err, "Unparsable level");
+            None
+        },
+    }
+}
+
+#[fn_fixture::snapshot("snapshot-tests/recorded")]
+fn evaluate(program: &str, snap: &mut fn_fixture::Recorder) -> Result<i64, String> {
+    let tokens: Vec<&str> = program.split_whitespace().collect();
+    snap.record("tokens", &tokens);
+    let mut stack = Vec::new();
+    for token in tokens {
+        let value = match token {
+            "+" | "*" | "/" => {
+                let (right, left) = stack
+                    .pop()
+                    .zip(stack.pop())
+                    .ok_or_else(|| format!("Missing operands for {}", token))?;
+                match token {
+                    "+" => left + right,
+                    "*" => left * right,
+                    _ => left / right,
+                }
+            },
+            number => number.parse().map_err(|err| format!("{}: {:?}", number, err))?,
+        };
+        stack.push(value);
+    }
+    snap.record("stack", &stack);
+    stack.pop().ok_or_else(|| "Empty program".to_string())
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Located {
+    test_path: &'static str,
+    name: &'static str,
+    dir: String,
+    notes: Result<String, std::io::ErrorKind>,
+}
+
+#[fn_fixture::snapshot("snapshot-tests/context")]
+fn locate(input: &str, context: &fn_fixture::Context) -> Located {
+    assert_eq!(input, context.name());
+    Located {
+        test_path: context.test_path(),
+        name: context.name(),
+        dir: context
+            .dir()
+            .strip_prefix(env!("CARGO_MANIFEST_DIR"))
+            .map_or_else(|_| format!("{:?}", context.dir()), |dir| dir.display().to_string().replace('\\', "/")),
+        notes: std::fs::read_to_string(context.resource("notes.txt")).map_err(|err| err.kind()),
+    }
+}
+
+#[fn_fixture::snapshot("snapshot-tests/resources", resources("include/**/*.h", "*.json"), strict)]
+fn preprocess(source: &str, context: &fn_fixture::Context) -> Result<Vec<String>, String> {
+    let resources = context.resources();
+    let mut lines = vec![format!("// resources: {:?}", resources.keys().collect::<Vec<_>>())];
+    for line in source.lines() {
+        match line.strip_prefix("#include ") {
+            Some(header) => {
+                let name = format!("include/{}", header.trim_matches('"'));
+                let header = resources
+                    .get(&*name)
+                    .ok_or_else(|| format!("Missing {}", name))?;
+                lines.extend(String::from_utf8_lossy(header).lines().map(str::to_string));
+            },
+            None => lines.push(line.to_string()),
+        }
+    }
+    Ok(lines)
+}
+
+// Each run leaks a count of its own, at an address of its own
+#[fn_fixture::snapshot("snapshot-tests/determinism", determinism = 2, redact(addresses))]
+fn count_at(text: &str) -> String {
+    let counted: &usize = Box::leak(Box::new(text.split_whitespace().count()));
+    format!("{} words at {:p}", counted, counted)
+}
+
+// Each line is what a run writes, compared as determinism would
+#[fn_fixture::snapshot("snapshot-tests/rewritten")]
+fn rewrite(runs: &str, context: &fn_fixture::Context) -> Result<(), Vec<String>> {
+    let key = context.test_path();
+    let mut lines = runs.lines();
+    let repeated = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        fn_fixture::runtime::determinism::repeat_writing(runs.lines().count(), key, || {
+            let out = fn_fixture::runtime::output_dir::OutputDir::new(key);
+            for (file, contents) in lines.next().unwrap().split_whitespace().filter_map(|part| part.split_once('=')) {
+                std::fs::write(out.join(file), contents).unwrap();
+            }
+            (String::new(), ())
+        })
+    }));
+    let _ = std::fs::remove_dir_all(fn_fixture::runtime::output_dir::OutputDir::new(key).path());
+    repeated
+        .map(|_| ())
+        .map_err(|panic| panic.downcast_ref::<String>().unwrap().lines().map(str::to_string).collect())
+}
+
+#[fn_fixture::snapshot("snapshot-tests/directories", determinism = 2)]
+fn bundle(root: &std::path::Path) -> Result<Vec<String>, String> {
+    let src = root.join("src");
+    let mut parts: Vec<_> = std::fs::read_dir(&src)
+        .map_err(|err| format!("Reading src: {:?}", err.kind()))?
+        .map(|entry| entry.map(|entry| entry.path()).map_err(|err| format!("{:?}", err.kind())))
+        .collect::<Result<_, _>>()?;
+    parts.retain(|part| part.extension().is_some_and(|extension| extension == "part"));
+    parts.sort();
+    let mut bundle = String::new();
+    for part in parts {
+        bundle.push_str(&std::fs::read_to_string(&part).map_err(|err| format!("{:?}", err.kind()))?);
+        std::fs::remove_file(&part).map_err(|err| format!("{:?}", err.kind()))?;
+    }
+    std::fs::write(root.join("bundle.txt"), &bundle).map_err(|err| format!("{:?}", err.kind()))?;
+    let mut files = vec![];
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(|err| format!("{:?}", err.kind()))? {
+            let path = entry.map_err(|err| format!("{:?}", err.kind()))?.path();
+            if path.is_dir() {
+                pending.push(path.clone());
+            }
+            files.push(path
+                .strip_prefix(root)
+                .map_err(|err| format!("{:?}", err))?
+                .display()
+                .to_string()
+                .replace('\\', "/"));
+        }
+    }
+    files.sort();
+    files.push(bundle);
+    Ok(files)
+}
+
+#[fn_fixture::snapshot("snapshot-tests/generated")]
+fn generate(schema: &str, out: &fn_fixture::OutputDir) -> Result<usize, String> {
+    let write = |name: &str, contents: String| std::fs::write(out.join(name), contents)
+        .map_err(|err| format!("Writing {}: {:?}", name, err.kind()));
+    std::fs::create_dir_all(out.join("types")).map_err(|err| format!("{:?}", err.kind()))?;
+    let mut modules = String::new();
+    for line in schema.lines() {
+        let (name, fields) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Expected `name: fields`, received {:?}", line))?;
+        let name = name.trim();
+        let fields: String = fields
+            .split(',')
+            .map(|field| format!("    pub {}: String,\n", field.trim()))
+            .collect();
+        write(&format!("types/{}.rs", name.to_lowercase()), format!("pub struct {} {{\n{}}}\n", name, fields))?;
+        modules.push_str(&format!("pub mod {};\n", name.to_lowercase()));
+    }
+    write("types.rs", modules)?;
+    Ok(schema.lines().count())
+}
+
+#[fn_fixture::snapshot("snapshot-tests/ignored", strict)]
+fn shout(input: &str) -> String {
+    input.to_uppercase()
+}
+
+#[fn_fixture::snapshot("snapshot-tests/configured")]
+fn tally(input: &str) -> std::collections::HashMap<String, usize> {
+    assert!(!input.trim().is_empty(), "No words to tally");
+    let mut counts = std::collections::HashMap::new();
+    for word in input.split_whitespace() {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[fn_fixture::snapshot("snapshot-tests/variants", variants(target_pointer_width = "32"), strict)]
+fn parse_size(input: &str) -> Result<usize, std::num::ParseIntError> {
+    input.trim().parse()
+}
+
+#[fn_fixture::snapshot("snapshot-tests/featured", output = "json")]
+fn split_words(input: &str) -> Vec<String> {
+    input
+        .split_whitespace()
+        .map(str::to_uppercase)
+        .collect()
+}
+
 #[fn_fixture::snapshot("snapshot-tests/examples")]
 fn parse_unsigned_number(value: &str) -> Result<usize, impl std::fmt::Debug> {
     value.parse()
@@ -26,7 +409,7 @@ fn parse_signed_number(value: &str) -> Result<isize, impl std::fmt::Debug> {
     value.parse()
 }
 
-#[fn_fixture::snapshot("snapshot-tests/source")]
+#[fn_fixture::snapshot("snapshot-tests/source", redact(manifest_dir, path_separators))]
 fn transform(
     params: (&str, &str),
 ) -> Result<
@@ -49,18 +432,9 @@ fn transform(
         Ok(value) => value,
         Err(value) => value,
     });
-
-    let search_path = format!("{:?}", Path::new(".")
-        .canonicalize()
-        .ok()
-        .as_ref()
-        .map(PathBuf::as_path)
-        .and_then(Path::to_str)
-        .expect("We have two parents")
-    );
-    let search_path_str = &search_path[0..(search_path.len() - 1)];
-    let double_path = format!("{:?}", &search_path_str[1..]);
-    let double_path_str = &double_path[0..(double_path.len() - 1)];
+    // Paths are redacted before formatting, so that the wrapping of
+    // lines does not depend on where the crate is checked out
+    let raw = PathSeparators.normalize(&ManifestDir(env!("CARGO_MANIFEST_DIR")).normalize(&raw));
 
     let fmt_result = format_input(
         Input::Text(raw),
@@ -73,17 +447,6 @@ fn transform(
                     format!("{}", contents)
                         .lines()
                         .map(str::to_string)
-                        .map(|line| {
-                            // All of this replacing is to make the tests less system-dependent.
-                            let mut new = line
-                                .replace(search_path_str, "\".")
-                                .replace(double_path_str, "\".")
-                                ;
-                            if line.len() != new.len() && std::path::MAIN_SEPARATOR == '\\' {
-                                new = new.replace("\\\\\\\\", "/").replace("\\\\", "/");
-                            }
-                            new
-                        })
                         .collect()
                 },
                 _ => unreachable!(),